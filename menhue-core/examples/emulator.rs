@@ -0,0 +1,16 @@
+//! Run an emulated bridge, for trying out menhue without a real one.
+//!
+//! The link button is pressed every time pairing is attempted.
+use std::thread;
+use std::time::Duration;
+
+use menhue_core::emulator::Bridge;
+
+fn main() -> std::io::Result<()> {
+    let bridge = Bridge::start()?;
+    println!("listening, run menhue with HOST={}", bridge.host());
+    loop {
+        bridge.press_link_button();
+        thread::sleep(Duration::from_secs(10));
+    }
+}
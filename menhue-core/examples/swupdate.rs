@@ -0,0 +1,63 @@
+//! Show the firmware update state of several bridges, and optionally
+//! install the updates that are ready.
+//!
+//! Usage: `swupdate [--install] username@host...`
+use std::time::Duration;
+
+use menhue_core::api::Client;
+use menhue_core::http::HttpClient;
+use menhue_core::session::Session;
+use menhue_core::settings::BridgeSettings;
+use menhue_core::swupdate::SoftwareUpdate;
+
+fn main() {
+    let mut install = false;
+    let mut bridges = Vec::new();
+    for arg in std::env::args().skip(1) {
+        if arg == "--install" {
+            install = true;
+        } else {
+            bridges.extend(BridgeSettings::parse_list(&arg));
+        }
+    }
+    if bridges.is_empty() {
+        eprintln!("usage: swupdate [--install] username@host...");
+        std::process::exit(2);
+    }
+
+    for bridge in bridges {
+        let host = bridge.host.clone();
+        let client = Client::new(Some(bridge.host), bridge.username);
+        client.set_clientkey(bridge.clientkey);
+        client.set_bridge_id(bridge.bridge_id);
+        let session = Session::new(HttpClient::new(Duration::from_secs(5)), client);
+        // `HttpClient` blocks, so the handlers are called right away
+        session.software_update({
+            let host = host.clone();
+            move |res| match res {
+                Ok(update) => print_status(&host, &update),
+                Err(err) => println!("{host}: {err}"),
+            }
+        });
+        if install {
+            session.install_update(move |res| match res {
+                Ok(true) => println!("{host}: installing"),
+                Ok(false) => println!("{host}: nothing to install"),
+                Err(err) => println!("{host}: {err}"),
+            });
+        }
+    }
+}
+
+fn print_status(host: &str, update: &SoftwareUpdate) {
+    println!(
+        "{host}: {:?} (bridge {:?}, last installed {})",
+        update.state,
+        update.bridge.state,
+        update.bridge.lastinstall.as_deref().unwrap_or("never"),
+    );
+    if let Some((hours, minutes)) = update.autoinstall.update_time() {
+        let on = if update.autoinstall.on { "on" } else { "off" };
+        println!("{host}: auto-install {on}, from {hours:02}:{minutes:02}");
+    }
+}
@@ -0,0 +1,526 @@
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use serde_json::{json, Map, Value};
+
+use crate::error::{HttpErrorBody, HueError, HueErrorKind};
+use crate::fixture::{redact_url, Secrets};
+use crate::light::StateUpdate;
+use crate::pairing::Credentials;
+use crate::tls::TrustPolicy;
+
+/// The HTTP methods used by the Hue API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Delete,
+}
+
+impl Method {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Get => "GET",
+            Self::Post => "POST",
+            Self::Put => "PUT",
+            Self::Delete => "DELETE",
+        }
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A request that is ready to be sent to the bridge.
+///
+/// The front-end is responsible for actually sending this, and for passing
+/// the response to [`parse_response`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    /// JSON-encoded body.
+    pub body: Option<Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// The request could not be sent, or no response was received.
+    Transport { url: String, description: String },
+    /// The request was cancelled before it finished, e.g. because the
+    /// bridge's certificate wasn't trusted.
+    Cancelled { url: String },
+    /// The bridge (or a proxy in front of it) responded with a non-2xx
+    /// status code.
+    Http {
+        url: String,
+        status_code: u16,
+        body: HttpErrorBody,
+    },
+    /// The response was not valid JSON.
+    Json {
+        url: String,
+        error: serde_json::Error,
+    },
+    /// The bridge responded with an error object.
+    Hue { url: String, error: HueError },
+    /// The response was valid JSON, but not in the shape we expected.
+    InvalidResponse { url: String, description: String },
+}
+
+impl Error {
+    /// The error reported by the bridge, if any.
+    pub fn hue_error(&self) -> Option<&HueError> {
+        match self {
+            Self::Hue { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+
+    /// The HTTP status code, if the request failed with one.
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            Self::Http { status_code, .. } => Some(*status_code),
+            _ => None,
+        }
+    }
+
+    /// Whether the username was rejected by the bridge.
+    ///
+    /// The v1 API reports this as an error object, while v2 responds with
+    /// 403 Forbidden.
+    pub fn is_unauthorized(&self) -> bool {
+        match self {
+            Self::Hue { error, .. } => error.kind == HueErrorKind::UnauthorizedUser,
+            Self::Http {
+                status_code: 401 | 403,
+                body: HttpErrorBody::Hue(_) | HttpErrorBody::V2(_),
+                ..
+            } => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the bridge is too busy to handle the request right now.
+    pub fn is_overloaded(&self) -> bool {
+        self.status_code() == Some(503)
+    }
+
+    /// Whether the response came from something that isn't a Hue bridge,
+    /// such as a proxy or captive portal.
+    pub fn is_from_proxy(&self) -> bool {
+        matches!(
+            self,
+            Self::Http {
+                status_code: 407 | 502 | 504,
+                ..
+            } | Self::Http {
+                body: HttpErrorBody::Html { .. },
+                ..
+            }
+        )
+    }
+}
+
+impl fmt::Display for Error {
+    /// The username in the URL is masked, so that errors can be shown and
+    /// logged.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport { url, description } => {
+                write!(f, "{}: {description}", redact_url(url))
+            }
+            Self::Cancelled { url } => write!(f, "{}: cancelled", redact_url(url)),
+            Self::Http {
+                url,
+                status_code,
+                body,
+            } => write!(f, "{}: HTTP status {status_code}: {body}", redact_url(url)),
+            Self::Json { url, error } => write!(f, "{}: invalid JSON: {error}", redact_url(url)),
+            Self::Hue { url, error } => write!(f, "{}: {error}", redact_url(url)),
+            Self::InvalidResponse { url, description } => {
+                write!(f, "{}: {description}", redact_url(url))
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Json { error, .. } => Some(error),
+            Self::Hue { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+fn parse_error(url: &str, json: &Value) -> Error {
+    match HueError::from_json(json) {
+        Some(error) => Error::Hue {
+            url: url.to_string(),
+            error,
+        },
+        None => Error::InvalidResponse {
+            url: url.to_string(),
+            description: "invalid error response object".to_string(),
+        },
+    }
+}
+
+pub(crate) fn parse_json(url: &str, status_code: u16, body: &[u8]) -> Result<Value, Error> {
+    if !(200..300).contains(&status_code) {
+        return Err(Error::Http {
+            url: url.to_string(),
+            status_code,
+            body: HttpErrorBody::parse(body),
+        });
+    }
+
+    serde_json::from_slice(body).map_err(|error| Error::Json {
+        url: url.to_string(),
+        error,
+    })
+}
+
+/// Interpret a response from the bridge.
+///
+/// The bridge wraps results in `success` and `error` objects; the former are
+/// unwrapped, and the first of the latter is turned into an [`Error`].
+///
+/// Use [`parse_update_response`] when the request changes several
+/// attributes at once.
+pub fn parse_response(url: &str, status_code: u16, body: &[u8]) -> Result<Value, Error> {
+    match parse_json(url, status_code, body)? {
+        Value::Array(array) => {
+            let mut result = Vec::with_capacity(array.len());
+
+            // Scan the array for errors
+            for item in array {
+                if let Value::Object(mut dict) = item {
+                    if let Some(error) = dict.get("error") {
+                        return Err(parse_error(url, error));
+                    }
+
+                    // Unwrap "success" key, if present
+                    if let Some(item) = dict.remove("success") {
+                        result.push(item);
+                        continue;
+                    }
+
+                    result.push(Value::Object(dict));
+                } else {
+                    result.push(item);
+                }
+            }
+
+            Ok(Value::Array(result))
+        }
+        Value::Object(mut dict) => {
+            if let Some(error) = dict.get("error") {
+                return Err(parse_error(url, error));
+            }
+
+            // Unwrap "success" key, if present
+            if let Some(json) = dict.remove("success") {
+                return Ok(json);
+            }
+
+            Ok(Value::Object(dict))
+        }
+        _ => Err(Error::InvalidResponse {
+            url: url.to_string(),
+            description: "response was neither an array nor an object".to_string(),
+        }),
+    }
+}
+
+/// A successfully changed attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Success {
+    /// E.g. `/lights/1/state/bri`.
+    pub address: String,
+    /// The new value of the attribute.
+    pub value: Value,
+}
+
+/// The outcome of a request that changes several attributes at once, such
+/// as `PUT /api/<username>/lights/<id>/state`.
+///
+/// Each attribute is applied separately by the bridge, so some may succeed
+/// while others fail.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UpdateResult {
+    pub successes: Vec<Success>,
+    pub errors: Vec<HueError>,
+}
+
+impl UpdateResult {
+    /// Whether every attribute was applied.
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// The new value of the attribute at `address`, if it was applied.
+    pub fn success(&self, address: &str) -> Option<&Value> {
+        self.successes
+            .iter()
+            .find(|success| success.address == address)
+            .map(|success| &success.value)
+    }
+
+    /// The error for the attribute at `address`, if it failed.
+    pub fn error(&self, address: &str) -> Option<&HueError> {
+        self.errors.iter().find(|error| error.address == address)
+    }
+}
+
+/// Interpret a response from the bridge, keeping every `success` and
+/// `error` item.
+///
+/// Only failing to get a response at all is reported as an [`Error`].
+pub fn parse_update_response(
+    url: &str,
+    status_code: u16,
+    body: &[u8],
+) -> Result<UpdateResult, Error> {
+    let json = parse_json(url, status_code, body)?;
+    let items = match json {
+        Value::Array(items) => items,
+        item @ Value::Object(_) => vec![item],
+        _ => {
+            return Err(Error::InvalidResponse {
+                url: url.to_string(),
+                description: "response was neither an array nor an object".to_string(),
+            })
+        }
+    };
+
+    let mut result = UpdateResult::default();
+    for item in items {
+        let invalid = || Error::InvalidResponse {
+            url: url.to_string(),
+            description: format!("invalid response item: {item}"),
+        };
+        let dict = item.as_object().ok_or_else(invalid)?;
+        if let Some(error) = dict.get("error") {
+            result
+                .errors
+                .push(HueError::from_json(error).ok_or_else(invalid)?);
+        } else if let Some(success) = dict.get("success") {
+            let success = success.as_object().ok_or_else(invalid)?;
+            result
+                .successes
+                .extend(success.iter().map(|(address, value)| Success {
+                    address: address.clone(),
+                    value: value.clone(),
+                }));
+        } else {
+            return Err(invalid());
+        }
+    }
+    Ok(result)
+}
+
+/// The details needed for talking to a bridge.
+///
+/// Cheap to clone, and clones share the host, username, client key and
+/// bridge id, such that logging in from one place is visible everywhere.
+#[derive(Debug, Clone, Default)]
+pub struct Client {
+    host: Rc<RefCell<Option<String>>>,
+    username: Rc<RefCell<Option<String>>>,
+    clientkey: Rc<RefCell<Option<String>>>,
+    bridge_id: Rc<RefCell<Option<String>>>,
+}
+
+impl Client {
+    pub fn new(host: Option<String>, username: Option<String>) -> Self {
+        Self {
+            host: Rc::new(RefCell::new(host)),
+            username: Rc::new(RefCell::new(username)),
+            clientkey: Rc::new(RefCell::new(None)),
+            bridge_id: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    pub fn host(&self) -> Option<String> {
+        self.host.borrow().clone()
+    }
+
+    pub fn set_host(&self, host: Option<String>) {
+        *self.host.borrow_mut() = host;
+    }
+
+    pub fn username(&self) -> Option<String> {
+        self.username.borrow().clone()
+    }
+
+    pub fn set_username(&self, username: Option<String>) {
+        *self.username.borrow_mut() = username;
+    }
+
+    /// The pre-shared key for entertainment streaming, in hex.
+    pub fn clientkey(&self) -> Option<String> {
+        self.clientkey.borrow().clone()
+    }
+
+    pub fn set_clientkey(&self, clientkey: Option<String>) {
+        *self.clientkey.borrow_mut() = clientkey;
+    }
+
+    pub fn bridge_id(&self) -> Option<String> {
+        self.bridge_id.borrow().clone()
+    }
+
+    pub fn set_bridge_id(&self, bridge_id: Option<String>) {
+        *self.bridge_id.borrow_mut() = bridge_id;
+    }
+
+    /// The policy for verifying the bridge's certificate when using HTTPS.
+    ///
+    /// `None` if we don't know the bridge id yet, in which case the bridge
+    /// can't be trusted.
+    pub fn trust_policy(&self) -> Option<TrustPolicy> {
+        self.bridge_id.borrow().as_deref().map(TrustPolicy::new)
+    }
+
+    pub(crate) fn url_with_scheme(&self, scheme: &str, path: &str) -> String {
+        let host = self.host.borrow();
+        let host = host
+            .as_deref()
+            .expect("host must be set before making URL request");
+        format!("{scheme}://{host}{path}")
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        // The v1 API is also available over HTTPS, but we only use that for
+        // v2, see `v2::Client`.
+        self.url_with_scheme("http", path)
+    }
+
+    pub fn request(&self, method: Method, path: &str, json: Option<&Value>) -> Request {
+        Request {
+            method,
+            url: self.url(path),
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: json.map(|json| serde_json::to_vec(json).expect("json writing")),
+        }
+    }
+
+    pub fn authenticated_path(&self, path: &str) -> String {
+        format!(
+            "/api/{}{path}",
+            self.username.borrow().as_deref().unwrap_or("not-set")
+        )
+    }
+
+    /// Get the bridge's public configuration, which doesn't need a username.
+    ///
+    /// Parse the response with [`parse_config`](crate::config::parse_config).
+    pub fn config_request(&self) -> Request {
+        self.request(Method::Get, "/api/config", None)
+    }
+
+    /// Get the bridge's full configuration, which needs a username.
+    ///
+    /// Parse the parts of it with
+    /// [`parse_whitelist`](crate::whitelist::parse_whitelist) and
+    /// [`parse_software_update`](crate::swupdate::parse_software_update).
+    pub fn full_config_request(&self) -> Request {
+        self.request(Method::Get, &self.authenticated_path("/config"), None)
+    }
+
+    /// Install the firmware updates that are ready, see
+    /// [`SoftwareUpdate::is_ready`](crate::swupdate::SoftwareUpdate::is_ready).
+    pub fn install_update_request(&self) -> Request {
+        self.request(
+            Method::Put,
+            &self.authenticated_path("/config"),
+            Some(&json!({ "swupdate2": { "install": true } })),
+        )
+    }
+
+    /// Ask the bridge to look for firmware updates now, instead of waiting
+    /// for its daily check.
+    pub fn check_for_update_request(&self) -> Request {
+        self.request(
+            Method::Put,
+            &self.authenticated_path("/config"),
+            Some(&json!({ "swupdate2": { "checkforupdate": true } })),
+        )
+    }
+
+    /// Remove a user from the bridge, see
+    /// [`Session::delete_user`](crate::session::Session::delete_user).
+    pub fn delete_user_request(&self, username: &str) -> Request {
+        self.request(
+            Method::Delete,
+            &self.authenticated_path(&format!("/config/whitelist/{username}")),
+            None,
+        )
+    }
+
+    /// Change the state of a light.
+    ///
+    /// Pass the response to [`parse_update_response`].
+    pub fn set_light_state(&self, light_id: &str, update: &StateUpdate) -> Request {
+        let json = serde_json::to_value(update).expect("serializing state update");
+        self.request(
+            Method::Put,
+            &self.authenticated_path(&format!("/lights/{light_id}/state")),
+            Some(&json),
+        )
+    }
+
+    /// The request that creates a new user on the bridge, see
+    /// [`pairing`](crate::pairing).
+    ///
+    /// Also asks for a client key, such that entertainment streaming works
+    /// without pairing again.
+    ///
+    /// Pass the result to [`Client::finish_connect`].
+    pub fn connect_request(&self, devicetype: &str) -> Request {
+        self.request(
+            Method::Post,
+            "/api",
+            Some(&json!({ "devicetype": devicetype, "generateclientkey": true })),
+        )
+    }
+
+    /// Store the username and client key from a successful
+    /// [`Client::connect_request`].
+    pub fn finish_connect(&self, url: &str, json: &Value) -> Result<Credentials, Error> {
+        // The response may have some of the credentials even if it's invalid
+        let invalid = |description: &str| Error::InvalidResponse {
+            url: url.to_string(),
+            description: format!("{description}: {}", Secrets::of(self).redact(json.clone())),
+        };
+
+        let dict: &Map<String, Value> = json
+            .as_array()
+            .and_then(|array| array.first())
+            .and_then(Value::as_object)
+            .ok_or_else(|| invalid("invalid response"))?;
+
+        let username = dict
+            .get("username")
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid("invalid username"))?;
+        let clientkey = dict
+            .get("clientkey")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+
+        self.set_username(Some(username.to_string()));
+        self.set_clientkey(clientkey.clone());
+        Ok(Credentials {
+            username: username.to_string(),
+            clientkey,
+        })
+    }
+}
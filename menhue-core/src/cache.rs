@@ -0,0 +1,140 @@
+//! The last known state of the lights, kept current by the event stream.
+use std::{cell::RefCell, rc::Rc};
+
+use crate::eventstream::{Event, EventType, ResourceEvent};
+use crate::light::{ColorMode, Light};
+use crate::v2::ResourceType;
+
+#[derive(Debug, Default)]
+struct Inner {
+    lights: Option<Vec<Light>>,
+    connected: bool,
+}
+
+/// Cached lights.
+///
+/// The cache is only used while connected to the event stream, otherwise
+/// we couldn't know whether it's up to date. Cheap to clone, and clones
+/// share the same cache.
+#[derive(Debug, Clone, Default)]
+pub struct LightCache {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl LightCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call when the event stream connects or disconnects.
+    ///
+    /// Events may have been missed while disconnected, so this clears the
+    /// cache.
+    pub fn set_connected(&self, connected: bool) {
+        let mut inner = self.inner.borrow_mut();
+        inner.connected = connected;
+        inner.lights = None;
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.inner.borrow().connected
+    }
+
+    /// Store freshly fetched lights.
+    pub fn set_lights(&self, lights: Vec<Light>) {
+        let mut inner = self.inner.borrow_mut();
+        if inner.connected {
+            inner.lights = Some(lights);
+        }
+    }
+
+    /// The cached lights, if they are known to be up to date.
+    pub fn lights(&self) -> Option<Vec<Light>> {
+        self.inner.borrow().lights.clone()
+    }
+
+    /// Apply an event from the stream.
+    ///
+    /// Returns whether any cached light changed.
+    pub fn apply(&self, event: &Event) -> bool {
+        let mut inner = self.inner.borrow_mut();
+        if inner.lights.is_none() {
+            return false;
+        }
+
+        let added = event.kind == EventType::Add
+            && event
+                .data
+                .iter()
+                .any(|resource| resource.rtype == ResourceType::Light);
+        if added {
+            // The event has the v2 representation and we need v1, so fetch
+            // everything again
+            inner.lights = None;
+            return true;
+        }
+
+        let lights = inner.lights.as_mut().expect("checked above");
+        let mut changed = false;
+        for resource in &event.data {
+            let Some(light_id) = light_id(resource) else {
+                continue;
+            };
+            match event.kind {
+                EventType::Update => {
+                    if let Some(light) = lights.iter_mut().find(|light| light.id == light_id) {
+                        changed |= apply_update(light, resource);
+                    }
+                }
+                EventType::Delete if resource.rtype == ResourceType::Light => {
+                    let len = lights.len();
+                    lights.retain(|light| light.id != light_id);
+                    changed |= lights.len() != len;
+                }
+                _ => {}
+            }
+        }
+        changed
+    }
+}
+
+/// The v1 light that a resource corresponds to.
+fn light_id(resource: &ResourceEvent) -> Option<&str> {
+    if !matches!(
+        resource.rtype,
+        ResourceType::Light | ResourceType::ZigbeeConnectivity
+    ) {
+        return None;
+    }
+    resource.id_v1.as_deref()?.strip_prefix("/lights/")
+}
+
+fn apply_update(light: &mut Light, resource: &ResourceEvent) -> bool {
+    let before = light.state.clone();
+    let state = &mut light.state;
+
+    if let Some(on) = resource.on {
+        state.on = Some(on.on);
+    }
+    if let Some(dimming) = resource.dimming {
+        state.bri = Some(brightness_to_bri(dimming.brightness));
+    }
+    if let Some(mirek) = resource.color_temperature.and_then(|ct| ct.mirek) {
+        state.ct = Some(mirek);
+        state.colormode = Some(ColorMode::Ct);
+    }
+    if let Some(color) = resource.color {
+        state.xy = Some([color.xy.x, color.xy.y]);
+        state.colormode = Some(ColorMode::Xy);
+    }
+    if let Some(status) = &resource.status {
+        state.reachable = Some(status == "connected");
+    }
+
+    light.state != before
+}
+
+/// Convert a v2 brightness in percent to a v1 `bri`.
+fn brightness_to_bri(brightness: f64) -> u8 {
+    (brightness * 2.54).round().clamp(1.0, 254.0) as u8
+}
@@ -0,0 +1,201 @@
+//! Converting between RGB and the CIE xy colors that lights are set with.
+//!
+//! This follows the conversion Philips documents for Hue lights: sRGB
+//! gamma, the wide gamut D65 matrix, and colors outside the light's gamut
+//! moved to the closest color inside it.
+use crate::light::{GamutType, Light};
+
+/// The triangle of colors a light can show, in CIE xy coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gamut {
+    pub red: [f64; 2],
+    pub green: [f64; 2],
+    pub blue: [f64; 2],
+}
+
+impl Gamut {
+    /// Living colors and the first LightStrips.
+    pub const A: Self = Self {
+        red: [0.704, 0.296],
+        green: [0.2151, 0.7106],
+        blue: [0.138, 0.08],
+    };
+    /// The first generations of color bulbs.
+    pub const B: Self = Self {
+        red: [0.675, 0.322],
+        green: [0.409, 0.518],
+        blue: [0.167, 0.04],
+    };
+    /// Current color lights.
+    pub const C: Self = Self {
+        red: [0.6915, 0.3083],
+        green: [0.17, 0.7],
+        blue: [0.1532, 0.0475],
+    };
+    /// All of xy, for lights whose gamut is unknown.
+    pub const FULL: Self = Self {
+        red: [1.0, 0.0],
+        green: [0.0, 1.0],
+        blue: [0.0, 0.0],
+    };
+
+    /// The gamut of a Philips gamut type; lights of other makes don't
+    /// have one.
+    pub fn of_type(gamut_type: GamutType) -> Option<Self> {
+        match gamut_type {
+            GamutType::A => Some(Self::A),
+            GamutType::B => Some(Self::B),
+            GamutType::C => Some(Self::C),
+            GamutType::Other => None,
+        }
+    }
+
+    /// The gamut the light reports, or else the one of its gamut type, or
+    /// else [`Self::FULL`].
+    pub fn of_light(light: &Light) -> Self {
+        let control = &light.capabilities.control;
+        control
+            .colorgamut
+            .map(Self::from)
+            .or_else(|| control.colorgamuttype.and_then(Self::of_type))
+            .unwrap_or(Self::FULL)
+    }
+
+    /// Whether `xy` is inside the gamut, or on its edges.
+    pub fn contains(&self, xy: [f64; 2]) -> bool {
+        // Which side of each edge the point is on; rounding at the edges is
+        // forgiven, so that the points from `closest` are inside
+        let side = |from: [f64; 2], to: [f64; 2]| {
+            (to[0] - from[0]) * (xy[1] - from[1]) - (to[1] - from[1]) * (xy[0] - from[0])
+        };
+        let sides = [
+            side(self.red, self.green),
+            side(self.green, self.blue),
+            side(self.blue, self.red),
+        ];
+        const EPSILON: f64 = 1e-9;
+        sides.iter().all(|s| *s >= -EPSILON) || sides.iter().all(|s| *s <= EPSILON)
+    }
+
+    /// The color in the gamut closest to `xy`, which is `xy` itself if it's
+    /// inside.
+    pub fn closest(&self, xy: [f64; 2]) -> [f64; 2] {
+        if self.contains(xy) {
+            return xy;
+        }
+        [
+            closest_on_segment(xy, self.red, self.green),
+            closest_on_segment(xy, self.green, self.blue),
+            closest_on_segment(xy, self.blue, self.red),
+        ]
+        .into_iter()
+        .min_by(|a, b| distance(xy, *a).total_cmp(&distance(xy, *b)))
+        .expect("a triangle has edges")
+    }
+}
+
+impl From<[[f64; 2]; 3]> for Gamut {
+    /// From the red, green and blue corners, as in
+    /// [`LightControl::colorgamut`](crate::light::LightControl::colorgamut).
+    fn from([red, green, blue]: [[f64; 2]; 3]) -> Self {
+        Self { red, green, blue }
+    }
+}
+
+fn closest_on_segment(p: [f64; 2], a: [f64; 2], b: [f64; 2]) -> [f64; 2] {
+    let ab = [b[0] - a[0], b[1] - a[1]];
+    let ap = [p[0] - a[0], p[1] - a[1]];
+    let t = ((ap[0] * ab[0] + ap[1] * ab[1]) / (ab[0] * ab[0] + ab[1] * ab[1])).clamp(0.0, 1.0);
+    [a[0] + ab[0] * t, a[1] + ab[1] * t]
+}
+
+fn distance(a: [f64; 2], b: [f64; 2]) -> f64 {
+    (a[0] - b[0]).hypot(a[1] - b[1])
+}
+
+/// Convert an sRGB color to xy in `gamut`, and its brightness from 0 to 1.
+///
+/// Black has no color, and is converted to the white point.
+pub fn rgb_to_xy(rgb: [u8; 3], gamut: &Gamut) -> ([f64; 2], f64) {
+    let [r, g, b] = rgb.map(|c| gamma_expand(f64::from(c) / 255.0));
+
+    let x = r * 0.664511 + g * 0.154324 + b * 0.162028;
+    let y = r * 0.283881 + g * 0.668433 + b * 0.047685;
+    let z = r * 0.000088 + g * 0.072310 + b * 0.986039;
+
+    let sum = x + y + z;
+    let xy = if sum == 0.0 {
+        WHITE
+    } else {
+        [x / sum, y / sum]
+    };
+    (gamut.closest(xy), y.min(1.0))
+}
+
+/// Convert xy in `gamut` and a brightness from 0 to 1 to sRGB, e.g. to
+/// show the current color of a light.
+///
+/// The color is scaled to be as bright as possible when the brightness
+/// would need more than full red, green or blue.
+pub fn xy_to_rgb(xy: [f64; 2], brightness: f64, gamut: &Gamut) -> [u8; 3] {
+    let [x, y] = gamut.closest(xy);
+    if y <= 0.0 || brightness <= 0.0 {
+        return [0, 0, 0];
+    }
+    let big_y = brightness.min(1.0);
+    let big_x = big_y / y * x;
+    let big_z = big_y / y * (1.0 - x - y);
+
+    let rgb = [
+        big_x * 1.656492 - big_y * 0.354851 - big_z * 0.255038,
+        -big_x * 0.707196 + big_y * 1.655397 + big_z * 0.036152,
+        big_x * 0.051713 - big_y * 0.121364 + big_z * 1.011530,
+    ]
+    .map(|c| c.max(0.0));
+    let max = rgb.iter().copied().fold(1.0, f64::max);
+    rgb.map(|c| (gamma_compress(c / max) * 255.0).round() as u8)
+}
+
+/// The xy of white, as converted from RGB.
+const WHITE: [f64; 2] = [0.3227, 0.329];
+
+fn gamma_expand(c: f64) -> f64 {
+    if c > 0.04045 {
+        ((c + 0.055) / 1.055).powf(2.4)
+    } else {
+        c / 12.92
+    }
+}
+
+fn gamma_compress(c: f64) -> f64 {
+    if c <= 0.0031308 {
+        12.92 * c
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// The brightness as a light's `bri`, from 1 to 254.
+pub fn brightness_to_bri(brightness: f64) -> u8 {
+    (brightness * 254.0).round().clamp(1.0, 254.0) as u8
+}
+
+/// A light's `bri` as a brightness from 0 to 1.
+pub fn bri_to_brightness(bri: u8) -> f64 {
+    f64::from(bri.min(254)) / 254.0
+}
+
+/// Parse a color like "#ff8800" or "ff8800".
+pub fn parse_hex(hex: &str) -> Option<[u8; 3]> {
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Format a color like "#ff8800".
+pub fn format_hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
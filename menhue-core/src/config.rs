@@ -0,0 +1,102 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+/// The bridge's public configuration, as returned from `GET /api/config`.
+///
+/// This doesn't need a username, so it can be used for checking that a
+/// host is a Hue bridge before pairing with it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct BridgeConfig {
+    pub name: String,
+    /// E.g. "001788FFFE6B1B41".
+    pub bridgeid: String,
+    /// "BSB002" for the square bridge.
+    pub modelid: String,
+    /// The version of the v1 API, e.g. "1.56.0".
+    pub apiversion: String,
+    /// The firmware version, e.g. "1956020000".
+    pub swversion: String,
+    /// Not reported by old firmware.
+    pub datastoreversion: Option<String>,
+}
+
+impl BridgeConfig {
+    /// The first bridge version to serve the v2 API.
+    pub const V2_API_VERSION: (u32, u32, u32) = (1, 48, 0);
+
+    /// [`BridgeConfig::apiversion`] as major, minor and patch version.
+    pub fn api_version(&self) -> Option<(u32, u32, u32)> {
+        let mut parts = self.apiversion.split('.').map(str::parse);
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+        let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+        Some((major, minor, patch))
+    }
+
+    /// Whether the bridge serves the v2 API, see [`v2`](crate::v2).
+    pub fn supports_v2(&self) -> bool {
+        self.api_version()
+            .is_some_and(|version| version >= Self::V2_API_VERSION)
+    }
+}
+
+pub fn parse_config(json: &Value) -> Result<BridgeConfig, serde_json::Error> {
+    BridgeConfig::deserialize(json)
+}
+
+/// Format a time like the bridge does, e.g. "2024-01-31T12:00:00", in UTC.
+pub fn format_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let (days, secs) = (secs / 86400, secs % 86400);
+
+    // Days since 1970-01-01 to a civil date, see
+    // <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// Parse a time in the format of [`format_timestamp`], in UTC.
+///
+/// Returns `None` for "none", which the bridge uses for times that haven't
+/// happened.
+pub fn parse_timestamp(s: &str) -> Option<SystemTime> {
+    let (date, time) = s.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let mut time = time.splitn(3, ':').map(str::parse::<u64>);
+    let (hour, minute, second) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return None;
+    }
+
+    // A civil date to days since 1970-01-01, see
+    // <https://howardhinnant.github.io/date_algorithms.html#days_from_civil>
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = u64::try_from(era * 146097 + doe - 719468).ok()?;
+
+    let secs = days * 86400 + hour * 3600 + minute * 60 + second;
+    UNIX_EPOCH.checked_add(Duration::from_secs(secs))
+}
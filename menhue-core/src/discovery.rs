@@ -0,0 +1,255 @@
+//! Finding bridges on the local network.
+//!
+//! This blocks the calling thread until the timeout is reached, so run it
+//! on a background thread.
+//!
+//! See <https://developers.meethue.com/develop/application-design-guidance/hue-bridge-discovery/>.
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::api::{parse_json, Client, Error};
+use crate::config::parse_config;
+use crate::http::HttpClient;
+
+pub mod mdns;
+pub mod nupnp;
+pub mod ssdp;
+
+/// Which discovery methods to use.
+///
+/// Each method can be pointed at a local stand-in instead of the real
+/// thing, or disabled with `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// How long to wait for answers, and for each bridge to respond when
+    /// checking it.
+    pub timeout: Duration,
+    /// Where to send the mDNS query.
+    pub mdns: Option<SocketAddr>,
+    /// Where to send the SSDP search.
+    pub ssdp: Option<SocketAddr>,
+    /// The URL of the discovery service; opt-in, see [`nupnp`].
+    pub nupnp: Option<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(3),
+            mdns: Some(mdns::MDNS_ADDR.into()),
+            ssdp: Some(ssdp::SSDP_ADDR.into()),
+            nupnp: None,
+        }
+    }
+}
+
+/// The outcome of [`discover`].
+#[derive(Debug, Default)]
+pub struct Discovered {
+    /// Bridges that answered `/api/config`.
+    pub bridges: Vec<Candidate>,
+    /// Why a method or a candidate failed; the others may still have found
+    /// bridges.
+    pub errors: Vec<String>,
+}
+
+/// Find bridges with all enabled methods at once, and check each of them.
+pub fn discover(options: &Options) -> Discovered {
+    let mut discovered = Discovered::default();
+    let mut candidates = Vec::new();
+
+    thread::scope(|scope| {
+        let timeout = options.timeout;
+        let mut methods = Vec::new();
+        if let Some(target) = options.mdns {
+            methods.push((
+                "mDNS",
+                scope.spawn(move || mdns::discover_at(target, timeout).map_err(|e| e.to_string())),
+            ));
+        }
+        if let Some(target) = options.ssdp {
+            methods.push((
+                "SSDP",
+                scope.spawn(move || ssdp::discover_at(target, timeout).map_err(|e| e.to_string())),
+            ));
+        }
+        if let Some(url) = &options.nupnp {
+            methods.push((
+                "N-UPnP",
+                scope.spawn(move || nupnp::discover(url, timeout).map_err(|e| e.to_string())),
+            ));
+        }
+
+        for (method, handle) in methods {
+            match handle.join().expect("discovery panicked") {
+                Ok(found) => candidates.extend(found),
+                Err(err) => discovered.errors.push(format!("{method}: {err}")),
+            }
+        }
+    });
+    dedup(&mut candidates);
+
+    let validated: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = candidates
+            .iter()
+            .map(|candidate| scope.spawn(|| validate(candidate, options.timeout)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("validation panicked"))
+            .collect()
+    });
+    for res in validated {
+        match res {
+            Ok(bridge) => discovered.bridges.push(bridge),
+            Err(err) => discovered.errors.push(err.to_string()),
+        }
+    }
+    // Two candidates may have turned out to be the same bridge
+    dedup(&mut discovered.bridges);
+
+    discovered
+}
+
+/// Check that there's a bridge at the candidate's address and port by
+/// fetching its public configuration, which doesn't need a username.
+///
+/// Returns the candidate with the bridge id from the configuration.
+pub fn validate(candidate: &Candidate, timeout: Duration) -> Result<Candidate, Error> {
+    let request = Client::new(Some(candidate.host()), None).config_request();
+    let url = request.url.clone();
+    let response = HttpClient::new(timeout).send(&request)?;
+    let json = parse_json(&url, response.status_code, &response.body)?;
+    let config = parse_config(&json).map_err(|err| Error::InvalidResponse {
+        url: url.clone(),
+        description: format!("not a Hue bridge: {err}"),
+    })?;
+
+    let bridge_id = normalize_bridge_id(&config.bridgeid);
+    if let Some(expected) = &candidate.bridge_id {
+        if *expected != bridge_id {
+            return Err(Error::InvalidResponse {
+                url,
+                description: format!("expected bridge {expected}, found {bridge_id}"),
+            });
+        }
+    }
+
+    Ok(Candidate {
+        bridge_id: Some(bridge_id),
+        ..candidate.clone()
+    })
+}
+
+/// A bridge that was found on the network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub address: IpAddr,
+    /// The port of the HTTPS API; the v1 API is also served over HTTP on
+    /// port 80.
+    ///
+    /// Anything answering on another port, like the
+    /// [emulator](crate::emulator), is expected to serve both there.
+    pub port: u16,
+    /// The id of the bridge, in uppercase like in `/api/config`, if the
+    /// discovery method reports it.
+    pub bridge_id: Option<String>,
+}
+
+impl Candidate {
+    /// The value for [`Settings::host`](crate::settings::Settings::host),
+    /// with the port unless it's the usual one.
+    pub fn host(&self) -> String {
+        let address = match self.address {
+            IpAddr::V4(address) => address.to_string(),
+            IpAddr::V6(address) => format!("[{address}]"),
+        };
+        if self.port == HTTPS_PORT {
+            address
+        } else {
+            format!("{address}:{}", self.port)
+        }
+    }
+}
+
+/// The port that bridges serve HTTPS on.
+const HTTPS_PORT: u16 = 443;
+
+/// Bridge ids are reported in lowercase by some methods and uppercase by
+/// others.
+fn normalize_bridge_id(bridge_id: &str) -> String {
+    bridge_id.trim().to_ascii_uppercase()
+}
+
+/// Merge candidates that refer to the same bridge, keeping the first.
+///
+/// Candidates are the same if they have the same bridge id, or if one of
+/// them doesn't know the id, the same address.
+fn dedup(candidates: &mut Vec<Candidate>) {
+    let mut merged: Vec<Candidate> = Vec::with_capacity(candidates.len());
+    for candidate in candidates.drain(..) {
+        let existing =
+            merged.iter_mut().find(
+                |existing| match (&existing.bridge_id, &candidate.bridge_id) {
+                    (Some(existing), Some(bridge_id)) => existing == bridge_id,
+                    _ => existing.address == candidate.address,
+                },
+            );
+        match existing {
+            Some(existing) => {
+                if existing.bridge_id.is_none() {
+                    existing.bridge_id = candidate.bridge_id;
+                }
+            }
+            None => merged.push(candidate),
+        }
+    }
+    *candidates = merged;
+}
+
+/// Send `query` to `target` (repeatedly, in case it got lost), and pass
+/// each datagram that arrives to `handle` until `timeout` has passed.
+fn collect(
+    target: SocketAddr,
+    query: &[u8],
+    timeout: Duration,
+    mut handle: impl FnMut(&[u8], IpAddr),
+) -> io::Result<()> {
+    const QUERY_INTERVAL: Duration = Duration::from_secs(1);
+
+    let socket = match target {
+        SocketAddr::V4(_) => UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?,
+        SocketAddr::V6(_) => UdpSocket::bind((Ipv6Addr::UNSPECIFIED, 0))?,
+    };
+    if target.ip().is_multicast() && target.is_ipv4() {
+        socket.set_multicast_ttl_v4(255)?;
+    }
+
+    let deadline = Instant::now() + timeout;
+    let mut next_query = Instant::now();
+    let mut buf = [0; 9000];
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(());
+        }
+        if now >= next_query {
+            socket.send_to(query, target)?;
+            next_query = now + QUERY_INTERVAL;
+        }
+
+        let wait = deadline.min(next_query) - now;
+        socket.set_read_timeout(Some(wait.max(Duration::from_millis(1))))?;
+        match socket.recv_from(&mut buf) {
+            Ok((len, from)) => handle(&buf[..len], from.ip()),
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) => {}
+            Err(err) => return Err(err),
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! Browsing for `_hue._tcp.local` with multicast DNS.
+//!
+//! The query is sent from an ephemeral port, which makes responders answer
+//! us directly with unicast ("legacy unicast" in RFC 6762), so we don't
+//! need port 5353, which is usually taken by the system's responder.
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4};
+use std::time::Duration;
+
+use super::{collect, dedup, normalize_bridge_id, Candidate, HTTPS_PORT};
+
+pub const SERVICE: &str = "_hue._tcp.local";
+
+/// The multicast group and port of mDNS.
+pub const MDNS_ADDR: SocketAddrV4 = SocketAddrV4::new(Ipv4Addr::new(224, 0, 0, 251), 5353);
+
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const TYPE_TXT: u16 = 16;
+const TYPE_AAAA: u16 = 28;
+const TYPE_SRV: u16 = 33;
+
+/// Find bridges that announce themselves with mDNS.
+///
+/// Collects answers until `timeout` has passed.
+pub fn discover(timeout: Duration) -> io::Result<Vec<Candidate>> {
+    discover_at(MDNS_ADDR.into(), timeout)
+}
+
+/// Like [`discover`], but send the query to `target` instead of the
+/// multicast group, e.g. to a responder on localhost.
+pub fn discover_at(target: SocketAddr, timeout: Duration) -> io::Result<Vec<Candidate>> {
+    let mut answers = Answers::default();
+    collect(target, &query(SERVICE), timeout, |response, source| {
+        // Ignore anything that isn't a valid response
+        if let Some(records) = parse_response(response) {
+            answers.add(records, source);
+        }
+    })?;
+    Ok(answers.candidates())
+}
+
+/// A PTR question for `service`, asking for a unicast response.
+fn query(service: &str) -> Vec<u8> {
+    // ID, flags, one question, no records
+    let mut query = vec![0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+    for label in service.split('.') {
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0);
+    query.extend_from_slice(&TYPE_PTR.to_be_bytes());
+    // Class IN, with the "unicast response" bit
+    query.extend_from_slice(&0x8001u16.to_be_bytes());
+    query
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Record {
+    /// Lowercase, without the trailing dot.
+    name: String,
+    data: RecordData,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RecordData {
+    Ptr(String),
+    Srv { port: u16, target: String },
+    Txt(Vec<String>),
+    A(Ipv4Addr),
+    Aaaa(Ipv6Addr),
+    Other,
+}
+
+/// The records from all responses, keyed by name.
+#[derive(Debug, Default)]
+struct Answers {
+    /// Service instances, e.g. "hue bridge - 6b1b41._hue._tcp.local", in the
+    /// order they were found.
+    instances: Vec<String>,
+    srv: HashMap<String, (u16, String)>,
+    txt: HashMap<String, Vec<String>>,
+    addresses: HashMap<String, IpAddr>,
+    /// Where the response about an instance came from, used if there's no
+    /// address record.
+    sources: HashMap<String, IpAddr>,
+}
+
+impl Answers {
+    fn add(&mut self, records: Vec<Record>, source: IpAddr) {
+        for record in records {
+            match record.data {
+                RecordData::Ptr(instance) if record.name == SERVICE => {
+                    self.sources.entry(instance.clone()).or_insert(source);
+                    if !self.instances.contains(&instance) {
+                        self.instances.push(instance);
+                    }
+                }
+                RecordData::Srv { port, target } => {
+                    self.srv.insert(record.name, (port, target));
+                }
+                RecordData::Txt(entries) => {
+                    self.txt.insert(record.name, entries);
+                }
+                // Prefer IPv4, that's what the bridge's own config reports
+                RecordData::A(address) => {
+                    self.addresses.insert(record.name, address.into());
+                }
+                RecordData::Aaaa(address) => {
+                    self.addresses.entry(record.name).or_insert(address.into());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn candidates(&self) -> Vec<Candidate> {
+        let mut candidates: Vec<Candidate> = self
+            .instances
+            .iter()
+            .map(|instance| {
+                let (port, address) = match self.srv.get(instance) {
+                    Some((port, target)) => (*port, self.addresses.get(target)),
+                    None => (HTTPS_PORT, None),
+                };
+                let address = address
+                    .or_else(|| self.sources.get(instance))
+                    .copied()
+                    .expect("instances always have a source");
+                let bridge_id = self.txt.get(instance).and_then(|entries| {
+                    entries
+                        .iter()
+                        .find_map(|entry| entry.strip_prefix("bridgeid="))
+                        .map(normalize_bridge_id)
+                });
+                Candidate {
+                    address,
+                    port,
+                    bridge_id,
+                }
+            })
+            .collect();
+        dedup(&mut candidates);
+        candidates
+    }
+}
+
+/// Parse the answers and additional records of a DNS response.
+fn parse_response(msg: &[u8]) -> Option<Vec<Record>> {
+    let header = msg.get(..12)?;
+    let flags = u16::from_be_bytes([header[2], header[3]]);
+    if flags & 0x8000 == 0 {
+        // A query, possibly our own
+        return None;
+    }
+    let count = |i: usize| u16::from_be_bytes([header[i], header[i + 1]]) as usize;
+    let (questions, records) = (count(4), count(6) + count(8) + count(10));
+
+    let mut pos = 12;
+    for _ in 0..questions {
+        pos = read_name(msg, pos)?.1 + 4;
+    }
+
+    let mut result = Vec::with_capacity(records);
+    for _ in 0..records {
+        let (name, next) = read_name(msg, pos)?;
+        let fixed = msg.get(next..next + 10)?;
+        let kind = u16::from_be_bytes([fixed[0], fixed[1]]);
+        let len = u16::from_be_bytes([fixed[8], fixed[9]]) as usize;
+        let start = next + 10;
+        let rdata = msg.get(start..start + len)?;
+        pos = start + len;
+
+        let data = match kind {
+            TYPE_PTR => RecordData::Ptr(read_name(msg, start)?.0),
+            TYPE_SRV if len >= 6 => RecordData::Srv {
+                port: u16::from_be_bytes([rdata[4], rdata[5]]),
+                target: read_name(msg, start + 6)?.0,
+            },
+            TYPE_TXT => RecordData::Txt(read_txt(rdata)),
+            TYPE_A => RecordData::A(<[u8; 4]>::try_from(rdata).ok()?.into()),
+            TYPE_AAAA => RecordData::Aaaa(<[u8; 16]>::try_from(rdata).ok()?.into()),
+            _ => RecordData::Other,
+        };
+        result.push(Record { name, data });
+    }
+    Some(result)
+}
+
+/// Read a possibly compressed name, returning it and the position after it.
+fn read_name(msg: &[u8], mut pos: usize) -> Option<(String, usize)> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut end = None;
+    // Guard against pointer loops
+    for _ in 0..128 {
+        let len = *msg.get(pos)? as usize;
+        match len {
+            0 => {
+                let name = labels.join(".").to_lowercase();
+                return Some((name, end.unwrap_or(pos + 1)));
+            }
+            0xc0.. => {
+                let offset = (len & 0x3f) << 8 | *msg.get(pos + 1)? as usize;
+                end.get_or_insert(pos + 2);
+                pos = offset;
+            }
+            _ => {
+                let label = msg.get(pos + 1..pos + 1 + len)?;
+                labels.push(String::from_utf8_lossy(label).into_owned());
+                pos += 1 + len;
+            }
+        }
+    }
+    None
+}
+
+fn read_txt(mut rdata: &[u8]) -> Vec<String> {
+    let mut entries = Vec::new();
+    while let Some((&len, rest)) = rdata.split_first() {
+        let len = (len as usize).min(rest.len());
+        entries.push(String::from_utf8_lossy(&rest[..len]).into_owned());
+        rdata = &rest[len..];
+    }
+    entries
+}
@@ -0,0 +1,54 @@
+//! Asking the Philips discovery service ("N-UPnP") for bridges.
+//!
+//! Bridges report their local address to the service, which returns the
+//! bridges that connect from the same public IP address as us. This sends
+//! a request to the internet, so it's opt-in.
+use std::net::IpAddr;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use super::{dedup, normalize_bridge_id, Candidate, HTTPS_PORT};
+use crate::api::{parse_json, Error, Method, Request};
+use crate::http::HttpClient;
+
+pub const DEFAULT_URL: &str = "https://discovery.meethue.com/";
+
+#[derive(Deserialize)]
+struct Entry {
+    id: String,
+    internalipaddress: IpAddr,
+    #[serde(default = "default_port")]
+    port: u16,
+}
+
+fn default_port() -> u16 {
+    HTTPS_PORT
+}
+
+/// Ask the discovery service at `url` (usually [`DEFAULT_URL`]).
+pub fn discover(url: &str, timeout: Duration) -> Result<Vec<Candidate>, Error> {
+    let request = Request {
+        method: Method::Get,
+        url: url.to_string(),
+        headers: vec![],
+        body: None,
+    };
+    let response = HttpClient::new(timeout).send(&request)?;
+    let json = parse_json(url, response.status_code, &response.body)?;
+    let entries: Vec<Entry> = serde_json::from_value(json).map_err(|error| Error::Json {
+        url: url.to_string(),
+        error,
+    })?;
+
+    let mut candidates = entries
+        .into_iter()
+        .map(|entry| Candidate {
+            address: entry.internalipaddress,
+            port: entry.port,
+            bridge_id: Some(normalize_bridge_id(&entry.id)),
+        })
+        .collect();
+    dedup(&mut candidates);
+    Ok(candidates)
+}
@@ -0,0 +1,76 @@
+//! Searching for bridges with SSDP (UPnP).
+//!
+//! Bridges answer an `M-SEARCH` with a `SERVER` header containing
+//! `IpBridge`, and their id in `hue-bridgeid`.
+use std::io::{self, BufReader};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::time::Duration;
+
+use super::{collect, dedup, normalize_bridge_id, Candidate, HTTPS_PORT};
+use crate::http::read_headers;
+
+/// The multicast group and port of SSDP.
+pub const SSDP_ADDR: SocketAddrV4 = SocketAddrV4::new(Ipv4Addr::new(239, 255, 255, 250), 1900);
+
+/// Find bridges that answer an SSDP search.
+///
+/// Collects answers until `timeout` has passed.
+pub fn discover(timeout: Duration) -> io::Result<Vec<Candidate>> {
+    discover_at(SSDP_ADDR.into(), timeout)
+}
+
+/// Like [`discover`], but send the search to `target` instead of the
+/// multicast group, e.g. to a responder on localhost.
+pub fn discover_at(target: SocketAddr, timeout: Duration) -> io::Result<Vec<Candidate>> {
+    let search = format!(
+        "M-SEARCH * HTTP/1.1\r\n\
+         HOST: {SSDP_ADDR}\r\n\
+         MAN: \"ssdp:discover\"\r\n\
+         MX: {}\r\n\
+         ST: ssdp:all\r\n\
+         \r\n",
+        timeout.as_secs().clamp(1, 5),
+    );
+
+    let mut candidates = Vec::new();
+    collect(target, search.as_bytes(), timeout, |response, source| {
+        if let Some(candidate) = parse_response(response, source) {
+            candidates.push(candidate);
+        }
+    })?;
+    dedup(&mut candidates);
+    Ok(candidates)
+}
+
+fn parse_response(response: &[u8], source: IpAddr) -> Option<Candidate> {
+    let mut reader = BufReader::new(response);
+    let mut status_line = String::new();
+    io::BufRead::read_line(&mut reader, &mut status_line).ok()?;
+    if !status_line.starts_with("HTTP/1.1 200") {
+        return None;
+    }
+    let headers = read_headers(&mut reader).ok()?;
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    if !header("server")?.contains("IpBridge") {
+        return None;
+    }
+
+    // E.g. `http://192.168.1.2:80/description.xml`
+    let address = header("location")
+        .and_then(|location| location.strip_prefix("http://"))
+        .and_then(|location| location.split(['/', ':']).next())
+        .and_then(|host| host.parse().ok())
+        .unwrap_or(source);
+
+    Some(Candidate {
+        address,
+        port: HTTPS_PORT,
+        bridge_id: header("hue-bridgeid").map(normalize_bridge_id),
+    })
+}
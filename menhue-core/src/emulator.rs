@@ -0,0 +1,735 @@
+//! A pretend bridge serving the v1 API on localhost, for testing and demos
+//! without real hardware.
+//!
+//! It keeps lights, groups and scenes in memory, and answers with the same
+//! `success` and `error` arrays as a real bridge. Point a
+//! [`Client`](crate::api::Client) at [`Bridge::host`] to use it.
+//!
+//! Failures can be injected: pairing only works after
+//! [`Bridge::press_link_button`], lights can be made unreachable through
+//! [`Bridge::with_state`], and [`Bridge::set_delay`] and
+//! [`Bridge::fail_next`] slow down or break the following responses.
+use std::collections::{BTreeMap, VecDeque};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use serde_json::{json, Map, Value};
+
+use crate::config::format_timestamp;
+use crate::http::read_headers;
+
+/// How long pairing works after pressing the link button.
+const LINK_BUTTON_WINDOW: Duration = Duration::from_secs(30);
+
+/// A failure to answer the next request with, see [`Bridge::fail_next`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+    /// Respond with this HTTP status code and an empty body.
+    Status(u16),
+    /// Respond with a v1 error of this type, e.g. 901 for an internal error.
+    Hue(u16),
+    /// Close the connection without responding.
+    Disconnect,
+}
+
+/// A user in the whitelist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    pub devicetype: String,
+    pub clientkey: Option<String>,
+    /// In the bridge's format, e.g. "2024-01-31T12:00:00".
+    pub create_date: String,
+    pub last_use_date: String,
+}
+
+/// Everything the bridge knows.
+///
+/// Lights, groups and scenes are stored as the JSON that the bridge
+/// returns for them, keyed by id.
+#[derive(Debug, Clone)]
+pub struct State {
+    pub name: String,
+    /// Uppercase, e.g. "001788FFFE6B1B41".
+    pub bridge_id: String,
+    pub apiversion: String,
+    pub swversion: String,
+    /// Keyed by username.
+    pub users: BTreeMap<String, User>,
+    pub lights: BTreeMap<String, Value>,
+    pub groups: BTreeMap<String, Value>,
+    pub scenes: BTreeMap<String, Value>,
+    /// Whether users may be deleted through the local API, which newer
+    /// bridges refuse.
+    pub local_user_deletion: bool,
+    /// `swupdate2` in the config; set its `state` to "anyreadytoinstall"
+    /// to offer an update.
+    pub swupdate: Value,
+    link_button: Option<Instant>,
+    delay: Duration,
+    failures: VecDeque<Failure>,
+    next_id: u64,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            name: "Emulated bridge".to_string(),
+            bridge_id: "001788FFFE000001".to_string(),
+            apiversion: "1.56.0".to_string(),
+            swversion: "1956020000".to_string(),
+            users: BTreeMap::new(),
+            lights: BTreeMap::new(),
+            groups: BTreeMap::new(),
+            scenes: BTreeMap::new(),
+            local_user_deletion: false,
+            swupdate: json!({
+                "checkforupdate": false,
+                "lastchange": "2024-01-01T03:00:00",
+                "bridge": { "state": "noupdates", "lastinstall": "2024-01-01T03:00:00" },
+                "state": "noupdates",
+                "autoinstall": { "updatetime": "T14:00:00", "on": true },
+            }),
+            link_button: None,
+            delay: Duration::ZERO,
+            failures: VecDeque::new(),
+            next_id: 1,
+        }
+    }
+}
+
+impl State {
+    /// A bridge with a dimmable light, a color light, a plug, a room and a
+    /// scene.
+    pub fn demo() -> Self {
+        let mut state = Self::default();
+        state.lights.insert(
+            "1".to_string(),
+            light("Desk", "Dimmable light", "LWB010", json!({})),
+        );
+        state.lights.insert(
+            "2".to_string(),
+            light(
+                "Ceiling",
+                "Extended color light",
+                "LCT015",
+                json!({
+                    "colorgamuttype": "C",
+                    "colorgamut": [[0.6915, 0.3083], [0.17, 0.7], [0.1532, 0.0475]],
+                    "ct": { "min": 153, "max": 500 },
+                }),
+            ),
+        );
+        state.lights.insert(
+            "3".to_string(),
+            json!({
+                "name": "Plug",
+                "type": "On/Off plug-in unit",
+                "modelid": "LOM001",
+                "state": { "on": false, "alert": "none", "mode": "homeautomation", "reachable": true },
+            }),
+        );
+        state.groups.insert(
+            "1".to_string(),
+            json!({
+                "name": "Office",
+                "type": "Room",
+                "class": "Office",
+                "lights": ["1", "2"],
+                "action": { "on": false, "bri": 254 },
+            }),
+        );
+        state.scenes.insert(
+            "demo-bright".to_string(),
+            json!({
+                "name": "Bright",
+                "type": "GroupScene",
+                "group": "1",
+                "lights": ["1", "2"],
+                "recycle": false,
+                "locked": false,
+                "lightstates": {
+                    "1": { "on": true, "bri": 254 },
+                    "2": { "on": true, "bri": 254, "ct": 233 },
+                },
+            }),
+        );
+        state
+    }
+
+    /// Add a user directly, as if it had been paired.
+    pub fn add_user(&mut self, username: &str, devicetype: &str) {
+        let now = format_timestamp(SystemTime::now());
+        self.users.insert(
+            username.to_string(),
+            User {
+                devicetype: devicetype.to_string(),
+                clientkey: None,
+                create_date: now.clone(),
+                last_use_date: now,
+            },
+        );
+    }
+
+    /// Mark a light as (un)reachable.
+    pub fn set_reachable(&mut self, light_id: &str, reachable: bool) {
+        if let Some(light) = self.lights.get_mut(light_id) {
+            light["state"]["reachable"] = json!(reachable);
+        }
+    }
+
+    fn public_config(&self) -> Value {
+        json!({
+            "name": self.name,
+            "datastoreversion": "126",
+            "swversion": self.swversion,
+            "apiversion": self.apiversion,
+            "mac": "00:17:88:00:00:01",
+            "bridgeid": self.bridge_id,
+            "factorynew": false,
+            "replacesbridgeid": null,
+            "modelid": "BSB002",
+            "starterkitid": "",
+        })
+    }
+
+    fn config(&self) -> Value {
+        let mut config = self.public_config();
+        let whitelist: Map<String, Value> = self
+            .users
+            .iter()
+            .map(|(username, user)| {
+                let entry = json!({
+                    "name": user.devicetype,
+                    "create date": user.create_date,
+                    "last use date": user.last_use_date,
+                });
+                (username.clone(), entry)
+            })
+            .collect();
+        config["whitelist"] = Value::Object(whitelist);
+        config["linkbutton"] = json!(self.link_button_pressed());
+        config["swupdate2"] = self.swupdate.clone();
+        config
+    }
+
+    fn link_button_pressed(&self) -> bool {
+        self.link_button
+            .is_some_and(|pressed| pressed.elapsed() < LINK_BUTTON_WINDOW)
+    }
+
+    fn new_id(&mut self) -> String {
+        let id = self.next_id;
+        self.next_id += 1;
+        format!("{id:016x}")
+    }
+}
+
+fn light(name: &str, kind: &str, modelid: &str, control: Value) -> Value {
+    let mut state = json!({
+        "on": false,
+        "bri": 127,
+        "alert": "none",
+        "mode": "homeautomation",
+        "reachable": true,
+    });
+    if control.get("ct").is_some() {
+        state["ct"] = json!(366);
+        state["colormode"] = json!("ct");
+    }
+    if control.get("colorgamut").is_some() {
+        state["hue"] = json!(8418);
+        state["sat"] = json!(140);
+        state["xy"] = json!([0.4573, 0.41]);
+        state["effect"] = json!("none");
+    }
+    json!({
+        "name": name,
+        "type": kind,
+        "modelid": modelid,
+        "manufacturername": "Signify Netherlands B.V.",
+        "state": state,
+        "swupdate": { "state": "noupdates", "lastinstall": "2024-01-01T03:00:00" },
+        "capabilities": { "certified": true, "control": control },
+    })
+}
+
+/// An emulated bridge, listening on localhost until dropped.
+#[derive(Debug)]
+pub struct Bridge {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl Bridge {
+    /// Start a bridge with [`State::demo`].
+    pub fn start() -> io::Result<Self> {
+        Self::with_initial_state(State::demo())
+    }
+
+    pub fn with_initial_state(state: State) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(state));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let bridge = Self {
+            addr,
+            state: state.clone(),
+            stopped: stopped.clone(),
+        };
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+                let Ok(stream) = stream else { continue };
+                let state = state.clone();
+                thread::spawn(move || serve(stream, &state));
+            }
+        });
+        Ok(bridge)
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The value for [`Settings::host`](crate::settings::Settings::host).
+    pub fn host(&self) -> String {
+        self.addr.to_string()
+    }
+
+    /// Look at or change the bridge's state.
+    pub fn with_state<R>(&self, f: impl FnOnce(&mut State) -> R) -> R {
+        f(&mut self.state.lock().expect("bridge state poisoned"))
+    }
+
+    /// Allow pairing for the next 30 seconds.
+    pub fn press_link_button(&self) {
+        self.with_state(|state| state.link_button = Some(Instant::now()));
+    }
+
+    /// Wait this long before answering each request.
+    pub fn set_delay(&self, delay: Duration) {
+        self.with_state(|state| state.delay = delay);
+    }
+
+    /// Answer the next request with `failure` instead of handling it.
+    ///
+    /// Failures are used up in the order they were added.
+    pub fn fail_next(&self, failure: Failure) {
+        self.with_state(|state| state.failures.push_back(failure));
+    }
+}
+
+impl Drop for Bridge {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        // Wake up the listener
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+struct HttpRequest {
+    method: String,
+    path: String,
+    body: Vec<u8>,
+}
+
+fn read_request(reader: &mut impl BufRead) -> io::Result<Option<HttpRequest>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid request line",
+        ));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let headers = read_headers(reader)?;
+    let len = headers
+        .iter()
+        .find(|(field, _)| field == "content-length")
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body)?;
+    Ok(Some(HttpRequest { method, path, body }))
+}
+
+/// Handle requests on a connection until the client closes it.
+fn serve(stream: TcpStream, state: &Mutex<State>) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(stream);
+    while let Ok(Some(request)) = read_request(&mut reader) {
+        let (delay, failure) = {
+            let mut state = state.lock().expect("bridge state poisoned");
+            (state.delay, state.failures.pop_front())
+        };
+        thread::sleep(delay);
+
+        let (status, body) = match failure {
+            Some(Failure::Disconnect) => return,
+            Some(Failure::Status(status)) => (status, Vec::new()),
+            Some(Failure::Hue(code)) => (
+                200,
+                error(code, &request.path, "injected failure")
+                    .to_string()
+                    .into_bytes(),
+            ),
+            None => {
+                let mut state = state.lock().expect("bridge state poisoned");
+                let json = handle(&mut state, &request.method, &request.path, &request.body);
+                (200, json.to_string().into_bytes())
+            }
+        };
+
+        let head = format!(
+            "HTTP/1.1 {status} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+            reason(status),
+            body.len()
+        );
+        if writer.write_all(head.as_bytes()).is_err() || writer.write_all(&body).is_err() {
+            return;
+        }
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
+
+fn error(code: u16, address: &str, description: &str) -> Value {
+    json!([{ "error": { "type": code, "address": address, "description": description } }])
+}
+
+fn not_available(address: &str) -> Value {
+    error(3, address, &format!("resource, {address}, not available"))
+}
+
+/// Answer a request, as the bridge would.
+fn handle(state: &mut State, method: &str, path: &str, body: &[u8]) -> Value {
+    let path = path.split('?').next().unwrap_or_default();
+    let segments: Vec<&str> = path
+        .trim_matches('/')
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    let body = if body.is_empty() {
+        Value::Null
+    } else {
+        match serde_json::from_slice(body) {
+            Ok(body) => body,
+            Err(_) => return error(2, "", "body contains invalid json"),
+        }
+    };
+
+    match (method, segments.as_slice()) {
+        ("POST", ["api"]) => create_user(state, &body),
+        ("GET", ["api", "config"]) => state.public_config(),
+        (_, ["api", username, rest @ ..]) => {
+            let Some(user) = state.users.get_mut(*username) else {
+                return error(1, &format!("/{}", rest.join("/")), "unauthorized user");
+            };
+            user.last_use_date = format_timestamp(SystemTime::now());
+            handle_authenticated(state, method, rest, &body)
+        }
+        _ => error(
+            4,
+            path,
+            &format!("method, {method}, not available for resource, {path}"),
+        ),
+    }
+}
+
+fn create_user(state: &mut State, body: &Value) -> Value {
+    let Some(devicetype) = body.get("devicetype").and_then(Value::as_str) else {
+        return error(5, "/", "invalid/missing parameters in body");
+    };
+    if !state.link_button_pressed() {
+        return error(101, "", "link button not pressed");
+    }
+
+    let username = format!("{}{}", state.new_id(), state.new_id());
+    let clientkey = (body.get("generateclientkey") == Some(&json!(true)))
+        .then(|| format!("{}{}", state.new_id(), state.new_id()).to_uppercase());
+    let now = format_timestamp(SystemTime::now());
+    state.users.insert(
+        username.clone(),
+        User {
+            devicetype: devicetype.to_string(),
+            clientkey: clientkey.clone(),
+            create_date: now.clone(),
+            last_use_date: now,
+        },
+    );
+
+    let mut success = json!({ "username": username });
+    if let Some(clientkey) = clientkey {
+        success["clientkey"] = json!(clientkey);
+    }
+    json!([{ "success": success }])
+}
+
+fn handle_authenticated(state: &mut State, method: &str, path: &[&str], body: &Value) -> Value {
+    let address = format!("/{}", path.join("/"));
+    match (method, path) {
+        ("GET", []) => json!({
+            "lights": state.lights,
+            "groups": state.groups,
+            "scenes": list_scenes(state),
+            "config": state.config(),
+        }),
+        ("GET", ["config"]) => state.config(),
+        ("PUT", ["config"]) => set_config(state, body),
+        ("DELETE", ["config", "whitelist", username]) => {
+            if !state.local_user_deletion {
+                error(1, &address, "unauthorized user")
+            } else if state.users.remove(*username).is_some() {
+                json!([{ "success": format!("{address} deleted") }])
+            } else {
+                not_available(&address)
+            }
+        }
+        ("GET", ["lights"]) => json!(state.lights),
+        ("GET", ["groups"]) => json!(state.groups),
+        ("GET", ["scenes"]) => list_scenes(state),
+        ("GET", [kind @ ("lights" | "groups" | "scenes"), id]) => {
+            let resources = match *kind {
+                "lights" => &state.lights,
+                "groups" => &state.groups,
+                _ => &state.scenes,
+            };
+            match resources.get(*id) {
+                Some(resource) => resource.clone(),
+                None => not_available(&address),
+            }
+        }
+        ("PUT", ["lights", id, "state"]) => {
+            if !state.lights.contains_key(*id) {
+                return not_available(&address);
+            }
+            set_light_state(state, id, body)
+        }
+        ("PUT", ["groups", id, "action"]) => {
+            let Some(group) = state.groups.get(*id) else {
+                return not_available(&address);
+            };
+            let lights: Vec<String> = group["lights"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|id| id.as_str().map(str::to_string))
+                .collect();
+            set_group_action(state, id, &lights, body)
+        }
+        _ => error(
+            4,
+            &address,
+            &format!("method, {method}, not available for resource, {address}"),
+        ),
+    }
+}
+
+/// Only `swupdate2` can be changed.
+fn set_config(state: &mut State, body: &Value) -> Value {
+    let Some(changes) = body.as_object() else {
+        return error(2, "", "body contains invalid json");
+    };
+
+    let mut results = Vec::new();
+    for (key, value) in changes {
+        let Some(swupdate) = value.as_object().filter(|_| key == "swupdate2") else {
+            let address = format!("/config/{key}");
+            let error = error(6, &address, &format!("parameter, {key}, not available"));
+            results.extend(error.as_array().cloned().unwrap_or_default());
+            continue;
+        };
+        for (key, value) in swupdate {
+            let address = format!("/config/swupdate2/{key}");
+            match (key.as_str(), value) {
+                ("install", Value::Bool(true)) => {
+                    let ready = |update: &Value| {
+                        update
+                            .as_str()
+                            .is_some_and(|s| s.contains("readytoinstall"))
+                    };
+                    if ready(&state.swupdate["state"]) {
+                        state.swupdate["state"] = json!("installing");
+                    }
+                    if ready(&state.swupdate["bridge"]["state"]) {
+                        state.swupdate["bridge"]["state"] = json!("installing");
+                    }
+                }
+                ("checkforupdate", Value::Bool(check)) => {
+                    state.swupdate["checkforupdate"] = json!(check);
+                }
+                _ => {
+                    let description = format!("invalid value, {value}, for parameter, {key}");
+                    results.extend(
+                        error(7, &address, &description)
+                            .as_array()
+                            .cloned()
+                            .unwrap_or_default(),
+                    );
+                    continue;
+                }
+            }
+            results.push(json!({ "success": { address: value } }));
+        }
+    }
+    Value::Array(results)
+}
+
+/// The scenes, without their light states like the real bridge.
+fn list_scenes(state: &State) -> Value {
+    let scenes: Map<String, Value> = state
+        .scenes
+        .iter()
+        .map(|(id, scene)| {
+            let mut scene = scene.clone();
+            if let Some(scene) = scene.as_object_mut() {
+                scene.remove("lightstates");
+            }
+            (id.clone(), scene)
+        })
+        .collect();
+    Value::Object(scenes)
+}
+
+/// Check and apply each attribute separately, like the bridge.
+fn set_light_state(state: &mut State, id: &str, body: &Value) -> Value {
+    let Some(changes) = body.as_object() else {
+        return error(2, "", "body contains invalid json");
+    };
+    let light_state = &mut state.lights.get_mut(id).expect("light exists")["state"];
+    let turning_on = changes.get("on") == Some(&json!(true));
+    let is_on = light_state["on"] == json!(true) || turning_on;
+
+    let mut results = Vec::new();
+    for (key, value) in changes {
+        let address = format!("/lights/{id}/state/{key}");
+        if !matches!(key.as_str(), "on" | "transitiontime") && light_state.get(key).is_none() {
+            let error = error(6, &address, &format!("parameter, {key}, not available"));
+            results.extend(error.as_array().cloned().unwrap_or_default());
+            continue;
+        }
+        if !is_on && key != "on" && key != "transitiontime" {
+            let description = format!("parameter, {key}, is not modifiable. Device is set to off.");
+            let error = error(201, &address, &description);
+            results.extend(error.as_array().cloned().unwrap_or_default());
+            continue;
+        }
+        if !is_valid(key, value) {
+            let description = format!("invalid value, {value}, for parameter, {key}");
+            let error = error(7, &address, &description);
+            results.extend(error.as_array().cloned().unwrap_or_default());
+            continue;
+        }
+
+        if key != "transitiontime" {
+            light_state[key.as_str()] = value.clone();
+            match key.as_str() {
+                "xy" => light_state["colormode"] = json!("xy"),
+                "ct" => light_state["colormode"] = json!("ct"),
+                "hue" | "sat" => light_state["colormode"] = json!("hs"),
+                _ => {}
+            }
+        }
+        results.push(json!({ "success": { address: value } }));
+    }
+    Value::Array(results)
+}
+
+fn set_group_action(state: &mut State, id: &str, lights: &[String], body: &Value) -> Value {
+    let Some(changes) = body.as_object() else {
+        return error(2, "", "body contains invalid json");
+    };
+
+    if let Some(scene_id) = changes.get("scene") {
+        let address = format!("/groups/{id}/action/scene");
+        let lightstates = scene_id
+            .as_str()
+            .and_then(|scene_id| state.scenes.get(scene_id))
+            .and_then(|scene| scene.get("lightstates"))
+            .and_then(Value::as_object)
+            .cloned();
+        let Some(lightstates) = lightstates else {
+            return error(
+                7,
+                &address,
+                &format!("invalid value, {scene_id}, for parameter, scene"),
+            );
+        };
+        for (light_id, light_state) in lightstates {
+            if state.lights.contains_key(&light_id) {
+                set_light_state(state, &light_id, &light_state);
+            }
+        }
+        return json!([{ "success": { address: scene_id } }]);
+    }
+
+    let mut results = Vec::new();
+    for (key, value) in changes {
+        let address = format!("/groups/{id}/action/{key}");
+        if !is_valid(key, value) {
+            let description = format!("invalid value, {value}, for parameter, {key}");
+            results.extend(
+                error(7, &address, &description)
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default(),
+            );
+            continue;
+        }
+        for light_id in lights {
+            if state.lights.contains_key(light_id) {
+                set_light_state(state, light_id, &json!({ key: value }));
+            }
+        }
+        if let Some(group) = state.groups.get_mut(id) {
+            group["action"][key.as_str()] = value.clone();
+        }
+        results.push(json!({ "success": { address: value } }));
+    }
+    Value::Array(results)
+}
+
+fn is_valid(key: &str, value: &Value) -> bool {
+    let in_range = |min: u64, max: u64| value.as_u64().is_some_and(|n| (min..=max).contains(&n));
+    match key {
+        "on" => value.is_boolean(),
+        "bri" => in_range(1, 254),
+        "sat" => in_range(0, 254),
+        "hue" | "transitiontime" => in_range(0, 65535),
+        "ct" => in_range(153, 500),
+        "xy" => value.as_array().is_some_and(|xy| {
+            xy.len() == 2
+                && xy
+                    .iter()
+                    .all(|c| c.as_f64().is_some_and(|c| (0.0..=1.0).contains(&c)))
+        }),
+        "alert" => matches!(value.as_str(), Some("none" | "select" | "lselect")),
+        "effect" => matches!(value.as_str(), Some("none" | "colorloop")),
+        _ => true,
+    }
+}
@@ -0,0 +1,267 @@
+//! Errors reported by the bridge, or by whatever answered in its place.
+//!
+//! See <https://developers.meethue.com/develop/hue-api/error-messages/>.
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::fixture::{redact_path, redact_whitelist};
+
+/// An `error` object from a bridge response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HueError {
+    pub kind: HueErrorKind,
+    /// The resource or attribute that the error relates to, e.g.
+    /// `/lights/1/state/bri`.
+    pub address: String,
+    pub description: String,
+}
+
+impl HueError {
+    pub fn new(code: u16, address: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            kind: HueErrorKind::from_code(code),
+            address: address.into(),
+            description: description.into(),
+        }
+    }
+
+    /// Parse an `error` object.
+    ///
+    /// Returns `None` if the object is not a dictionary.
+    pub fn from_json(json: &Value) -> Option<Self> {
+        let json = json.as_object()?;
+        let code = json
+            .get("type")
+            .and_then(Value::as_u64)
+            .and_then(|code| u16::try_from(code).ok())
+            .unwrap_or(0);
+        let address = json
+            .get("address")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let description = match json.get("description") {
+            Some(Value::String(s)) => s.clone(),
+            Some(_) => "incorrect error description type".to_string(),
+            None => "no error description".to_string(),
+        };
+        Some(Self::new(code, address, description))
+    }
+
+    /// The numeric `type` of the error.
+    pub fn code(&self) -> u16 {
+        self.kind.code()
+    }
+}
+
+impl fmt::Display for HueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // TODO: Hue error is not localized
+
+        // Descriptions may repeat the address, as in "resource,
+        // /config/whitelist/<username>, not available"
+        let description = redact_whitelist(&self.description);
+        write!(f, "{description} (type {}", self.code())?;
+        if !self.address.is_empty() {
+            write!(f, " at {}", redact_path(&self.address))?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl std::error::Error for HueError {}
+
+/// The body of a response with a non-2xx status code.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HttpErrorBody {
+    Empty,
+    /// One or more v1 `error` objects.
+    Hue(Vec<HueError>),
+    /// The descriptions from a v2 `errors` array, e.g. "unauthorized user"
+    /// when the application key is wrong.
+    V2(Vec<String>),
+    /// Some other JSON.
+    Json(Value),
+    /// An HTML page, most likely from a proxy or the bridge's web server.
+    Html {
+        title: Option<String>,
+        html: String,
+    },
+    Text(String),
+}
+
+impl HttpErrorBody {
+    pub fn parse(body: &[u8]) -> Self {
+        let text = String::from_utf8_lossy(body);
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            return Self::Empty;
+        }
+
+        if let Ok(json) = serde_json::from_str::<Value>(trimmed) {
+            return Self::from_json(json);
+        }
+
+        if trimmed.starts_with('<') {
+            return Self::Html {
+                title: html_title(trimmed),
+                html: text.into_owned(),
+            };
+        }
+
+        Self::Text(text.into_owned())
+    }
+
+    fn from_json(json: Value) -> Self {
+        // v1: `{"error": {...}}` or `[{"error": {...}}, ...]`
+        let items = match &json {
+            Value::Array(items) => items.iter().collect(),
+            item @ Value::Object(_) => vec![item],
+            _ => vec![],
+        };
+        let errors: Vec<HueError> = items
+            .iter()
+            .filter_map(|item| item.get("error"))
+            .filter_map(HueError::from_json)
+            .collect();
+        if !errors.is_empty() {
+            return Self::Hue(errors);
+        }
+
+        // v2: `{"errors": [{"description": "..."}], "data": []}`
+        if let Some(Value::Array(errors)) = json.get("errors") {
+            return Self::V2(
+                errors
+                    .iter()
+                    .map(|error| match error.get("description") {
+                        Some(Value::String(description)) => description.clone(),
+                        _ => error.to_string(),
+                    })
+                    .collect(),
+            );
+        }
+
+        Self::Json(json)
+    }
+}
+
+impl fmt::Display for HttpErrorBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty body"),
+            Self::Hue(errors) => {
+                for (i, error) in errors.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{error}")?;
+                }
+                Ok(())
+            }
+            Self::V2(descriptions) => write!(f, "{}", descriptions.join(", ")),
+            Self::Json(json) => write!(f, "{json}"),
+            Self::Html {
+                title: Some(title), ..
+            } => write!(f, "HTML page {title:?}"),
+            Self::Html { title: None, .. } => write!(f, "HTML page"),
+            Self::Text(text) => {
+                let text = text.trim();
+                // Don't flood the logs with huge bodies
+                match text.char_indices().nth(200) {
+                    Some((i, _)) => write!(f, "{}...", &text[..i]),
+                    None => write!(f, "{text}"),
+                }
+            }
+        }
+    }
+}
+
+fn html_title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let start = lower.find("<title")?;
+    let start = start + lower[start..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    Some(html[start..end].trim().to_string())
+}
+
+macro_rules! error_kinds {
+    ($(
+        $(#[$m:meta])*
+        $name:ident = $code:literal,
+    )*) => {
+        /// The documented error types.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum HueErrorKind {
+            $(
+                $(#[$m])*
+                $name,
+            )*
+            /// An error type we don't know about.
+            Other(u16),
+        }
+
+        impl HueErrorKind {
+            pub fn from_code(code: u16) -> Self {
+                match code {
+                    $($code => Self::$name,)*
+                    code => Self::Other(code),
+                }
+            }
+
+            pub fn code(self) -> u16 {
+                match self {
+                    $(Self::$name => $code,)*
+                    Self::Other(code) => code,
+                }
+            }
+        }
+    };
+}
+
+error_kinds! {
+    /// The username is not (or no longer) whitelisted on the bridge; we need
+    /// to pair again.
+    UnauthorizedUser = 1,
+    /// The request body contained invalid JSON.
+    InvalidJson = 2,
+    /// The addressed resource does not exist.
+    ResourceNotAvailable = 3,
+    /// The HTTP method is not supported for the resource.
+    MethodNotAvailable = 4,
+    MissingParameters = 5,
+    ParameterNotAvailable = 6,
+    InvalidValue = 7,
+    /// The parameter is read-only.
+    ParameterNotModifiable = 8,
+    TooManyItems = 11,
+    PortalConnectionRequired = 12,
+    /// Pairing needs the link button on the bridge to be pressed first.
+    LinkButtonNotPressed = 101,
+    DhcpCannotBeDisabled = 110,
+    InvalidUpdateState = 111,
+    /// The parameter can't be changed while the light is turned off.
+    DeviceOff = 201,
+    GroupTableFull = 301,
+    DeviceGroupTableFull = 302,
+    GroupTypeNotModifiable = 305,
+    LightAlreadyInRoom = 306,
+    SceneCreationInProgress = 401,
+    SceneBufferFull = 402,
+    SensorTypeNotAllowed = 501,
+    SensorListFull = 502,
+    RuleEngineFull = 601,
+    ConditionError = 607,
+    ActionError = 608,
+    UnableToActivate = 609,
+    ScheduleListFull = 701,
+    InvalidScheduleTimeZone = 702,
+    ScheduleTimeAndLocalTime = 703,
+    CannotCreateSchedule = 704,
+    ScheduleTimeInPast = 705,
+    CommandError = 706,
+    SourceModelInvalid = 801,
+    SourceFactoryNew = 802,
+    InvalidState = 803,
+    /// Something went wrong inside the bridge; usually temporary.
+    InternalError = 901,
+}
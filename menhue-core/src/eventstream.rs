@@ -0,0 +1,182 @@
+//! The v2 event stream, which pushes changes to resources as they happen.
+//!
+//! The bridge keeps `GET /eventstream/clip/v2` open, and sends a
+//! `text/event-stream` message whenever something changes, including changes
+//! made from the Hue app or a wall switch.
+//!
+//! See <https://developers.meethue.com/develop/hue-api-v2/core-concepts/#events>.
+use std::mem;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::v2::{Color, ColorTemperature, Dimming, On, ResourceIdentifier, ResourceType};
+
+pub const PATH: &str = "/eventstream/clip/v2";
+
+/// A message from a `text/event-stream`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Message {
+    pub id: Option<String>,
+    /// The `event` field, if set.
+    pub event: Option<String>,
+    /// The `data` lines, joined by newlines.
+    pub data: String,
+}
+
+/// Splits a `text/event-stream` into [`Message`]s as it arrives.
+///
+/// See <https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation>.
+#[derive(Debug, Clone, Default)]
+pub struct Parser {
+    /// An incomplete line.
+    buffer: Vec<u8>,
+    message: Message,
+    has_data: bool,
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed the next chunk of the stream to the parser, and return the
+    /// messages that were completed by it.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<Message> {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut messages = Vec::new();
+        let mut start = 0;
+        while let Some(end) = self.buffer[start..].iter().position(|&b| b == b'\n') {
+            let line = &self.buffer[start..start + end];
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            let line = String::from_utf8_lossy(line).into_owned();
+            start += end + 1;
+
+            if let Some(message) = self.process_line(&line) {
+                messages.push(message);
+            }
+        }
+        self.buffer.drain(..start);
+        messages
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<Message> {
+        // An empty line dispatches the message
+        if line.is_empty() {
+            let message = mem::take(&mut self.message);
+            return mem::take(&mut self.has_data).then_some(message);
+        }
+
+        // Comments, the bridge sends ": hi" when connecting
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "data" => {
+                if self.has_data {
+                    self.message.data.push('\n');
+                }
+                self.message.data.push_str(value);
+                self.has_data = true;
+            }
+            "event" => self.message.event = Some(value.to_string()),
+            "id" => self.message.id = Some(value.to_string()),
+            // `retry` and unknown fields
+            _ => {}
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventType {
+    Update,
+    Add,
+    Delete,
+    Error,
+    #[serde(other)]
+    Other,
+}
+
+/// A resource that changed.
+///
+/// For updates, only the attributes that changed are set.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ResourceEvent {
+    pub id: String,
+    pub id_v1: Option<String>,
+    #[serde(rename = "type")]
+    pub rtype: ResourceType,
+    pub owner: Option<ResourceIdentifier>,
+    pub on: Option<On>,
+    pub dimming: Option<Dimming>,
+    pub color_temperature: Option<ColorTemperature>,
+    pub color: Option<Color>,
+    /// The status of a `zigbee_connectivity`, e.g. "connected" or
+    /// "connectivity_issue".
+    pub status: Option<String>,
+}
+
+/// An event, containing one or more changed resources.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Event {
+    pub id: String,
+    pub creationtime: String,
+    #[serde(rename = "type")]
+    pub kind: EventType,
+    #[serde(default)]
+    pub data: Vec<ResourceEvent>,
+}
+
+/// Parse the events in a message.
+///
+/// The bridge batches events, so each message contains an array of them.
+pub fn parse_events(message: &Message) -> Result<Vec<Event>, serde_json::Error> {
+    serde_json::from_str(&message.data)
+}
+
+/// The delay before reconnecting after the stream was interrupted.
+///
+/// Doubles on each consecutive failure, up to a maximum.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    attempt: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1), Duration::from_secs(60))
+    }
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            attempt: 0,
+        }
+    }
+
+    /// The delay before the next attempt.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self
+            .initial
+            .saturating_mul(2u32.saturating_pow(self.attempt))
+            .min(self.max);
+        self.attempt = self.attempt.saturating_add(1);
+        delay
+    }
+
+    /// Call when connected successfully.
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
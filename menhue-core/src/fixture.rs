@@ -0,0 +1,383 @@
+//! Recording the traffic with a bridge, and replaying it later.
+//!
+//! A fixture file has one [`Exchange`] per line, as JSON. The username and
+//! client key are replaced with placeholders, so that fixtures can be
+//! attached to bug reports.
+use std::{
+    cell::RefCell,
+    fmt, fs,
+    fs::File,
+    io::{self, Write},
+    path::Path,
+    rc::Rc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::api::{Client, Error, Request};
+use crate::transport::{Response, Transport};
+
+/// Replaces the username.
+pub const USERNAME: &str = "<username>";
+/// Replaces the client key.
+pub const CLIENTKEY: &str = "<clientkey>";
+
+/// A request and the response to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Exchange {
+    pub method: String,
+    /// Without the scheme and host.
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<Value>,
+    pub status_code: u16,
+    /// The response body, if it was JSON.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<Value>,
+    /// The response body, if it wasn't.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_text: Option<String>,
+}
+
+impl Exchange {
+    /// Redact the secrets known to `client`, and those in the response
+    /// itself.
+    fn new(client: &Client, request: &Request, response: &Response) -> Self {
+        let secrets = Secrets::of(client);
+        let json = |body: &[u8]| {
+            serde_json::from_slice(body)
+                .ok()
+                .map(|json| secrets.redact(json))
+        };
+        let response_json = json(&response.body);
+        Self {
+            method: request.method.as_str().to_string(),
+            path: redact_path(&request.url),
+            request: request.body.as_deref().and_then(json),
+            status_code: response.status_code,
+            response_text: (response_json.is_none() && !response.body.is_empty())
+                .then(|| secrets.redact_str(&String::from_utf8_lossy(&response.body))),
+            response: response_json,
+        }
+    }
+
+    fn matches(&self, request: &Request) -> bool {
+        self.method == request.method.as_str() && self.path == redact_path(&request.url)
+    }
+
+    fn to_response(&self) -> Response {
+        let body = match (&self.response, &self.response_text) {
+            (Some(json), _) => serde_json::to_vec(json).expect("json writing"),
+            (None, Some(text)) => text.clone().into_bytes(),
+            (None, None) => Vec::new(),
+        };
+        Response {
+            status_code: self.status_code,
+            headers: Vec::new(),
+            body,
+        }
+    }
+}
+
+/// The secrets to replace with placeholders.
+pub(crate) struct Secrets {
+    username: Option<String>,
+    clientkey: Option<String>,
+}
+
+impl Secrets {
+    pub(crate) fn of(client: &Client) -> Self {
+        Self {
+            username: client.username(),
+            clientkey: client.clientkey(),
+        }
+    }
+
+    pub(crate) fn redact_str(&self, s: &str) -> String {
+        let mut s = s.to_string();
+        for (secret, placeholder) in [(&self.username, USERNAME), (&self.clientkey, CLIENTKEY)] {
+            if let Some(secret) = secret.as_deref().filter(|secret| !secret.is_empty()) {
+                s = s.replace(secret, placeholder);
+            }
+        }
+        s
+    }
+
+    pub(crate) fn redact(&self, json: Value) -> Value {
+        match json {
+            Value::String(s) => Value::String(self.redact_str(&s)),
+            Value::Array(items) => items.into_iter().map(|item| self.redact(item)).collect(),
+            Value::Object(dict) => dict
+                .into_iter()
+                .map(|(key, value)| match (&*key, value) {
+                    // The response to pairing, before `Client` knows them
+                    ("username", Value::String(_)) => (key, USERNAME.into()),
+                    ("clientkey", Value::String(_)) => (key, CLIENTKEY.into()),
+                    // Other applications' usernames in `/config`
+                    ("whitelist", Value::Object(users)) => {
+                        let users = users
+                            .into_iter()
+                            .enumerate()
+                            .map(|(i, (username, user))| {
+                                let username = if Some(&username) == self.username.as_ref() {
+                                    USERNAME.to_string()
+                                } else {
+                                    format!("<user {i}>")
+                                };
+                                (username, self.redact(user))
+                            })
+                            .collect();
+                        (key, Value::Object(users))
+                    }
+                    (_, value) => (self.redact_str(&key), self.redact(value)),
+                })
+                .collect(),
+            json => json,
+        }
+    }
+}
+
+/// The path of `url`, with the username replaced by [`USERNAME`].
+///
+/// Requests made before pairing use a dummy username, which is replaced
+/// too, so that they match when replaying. So are other applications'
+/// usernames, see [`redact_whitelist`].
+pub fn redact_path(url: &str) -> String {
+    let path = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest.find('/').map_or("/", |i| &rest[i..]));
+    let path = match path.strip_prefix("/api/") {
+        Some(rest) => {
+            let (username, rest) = rest.split_once('/').unwrap_or((rest, ""));
+            if username == "config" || username.is_empty() {
+                path.to_string()
+            } else if rest.is_empty() {
+                format!("/api/{USERNAME}")
+            } else {
+                format!("/api/{USERNAME}/{rest}")
+            }
+        }
+        None => path.to_string(),
+    };
+    redact_whitelist(&path)
+}
+
+/// Replace the usernames in `/whitelist/<username>` with [`USERNAME`], e.g.
+/// of other applications when deleting them.
+pub fn redact_whitelist(s: &str) -> String {
+    const WHITELIST: &str = "/whitelist/";
+    let mut redacted = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find(WHITELIST) {
+        let start = i + WHITELIST.len();
+        redacted.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(|c: char| c == '/' || c == ',' || c.is_whitespace())
+            .map_or(rest.len(), |end| start + end);
+        if end > start {
+            redacted.push_str(USERNAME);
+        }
+        rest = &rest[end..];
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+/// `url` with the username replaced by [`USERNAME`], like [`redact_path`]
+/// but keeping the scheme and host, e.g. for error messages.
+pub fn redact_url(url: &str) -> String {
+    let origin = url.split_once("://").map_or("", |(scheme, rest)| {
+        let host_len = rest.find('/').unwrap_or(rest.len());
+        &url[..scheme.len() + "://".len() + host_len]
+    });
+    format!("{origin}{}", redact_path(url))
+}
+
+type ErrorObserver = Rc<dyn Fn(&io::Error)>;
+
+/// Passes requests on to another transport, and appends each response to
+/// a fixture file.
+///
+/// Requests that got no response at all are not recorded.
+pub struct Recorder<T> {
+    inner: T,
+    client: Client,
+    file: Rc<RefCell<File>>,
+    on_error: Rc<RefCell<Option<ErrorObserver>>>,
+}
+
+impl<T: fmt::Debug> fmt::Debug for Recorder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recorder")
+            .field("inner", &self.inner)
+            .field("file", &self.file)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Recorder<T> {
+    /// Record to the file at `path`, appending if it exists.
+    ///
+    /// `client` is the one whose requests are sent, for redacting its
+    /// username and client key.
+    pub fn new(inner: T, client: Client, path: &Path) -> io::Result<Self> {
+        let file = File::options().create(true).append(true).open(path)?;
+        Ok(Self {
+            inner,
+            client,
+            file: Rc::new(RefCell::new(file)),
+            on_error: Rc::default(),
+        })
+    }
+
+    /// Called when writing to the file fails.
+    pub fn set_on_error(&self, on_error: impl Fn(&io::Error) + 'static) {
+        *self.on_error.borrow_mut() = Some(Rc::new(on_error));
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T: Transport> Transport for Recorder<T> {
+    fn send(&self, request: Request, completion_handler: Box<dyn FnOnce(Result<Response, Error>)>) {
+        let client = self.client.clone();
+        let file = self.file.clone();
+        let on_error = self.on_error.clone();
+        let recorded = request.clone();
+        self.inner.send(
+            request,
+            Box::new(move |res| {
+                if let Ok(response) = &res {
+                    let exchange = Exchange::new(&client, &recorded, response);
+                    let mut line = serde_json::to_vec(&exchange).expect("json writing");
+                    line.push(b'\n');
+                    if let Err(err) = file.borrow_mut().write_all(&line) {
+                        let on_error = on_error.borrow().clone();
+                        if let Some(on_error) = on_error {
+                            on_error(&err);
+                        }
+                    }
+                }
+                completion_handler(res)
+            }),
+        );
+    }
+
+    fn run_after(&self, delay: Duration, f: Box<dyn FnOnce()>) {
+        self.inner.run_after(delay, f)
+    }
+}
+
+type Delayed = (Instant, Box<dyn FnOnce()>);
+
+/// Answers requests from a fixture.
+///
+/// Requests are answered right away. Work scheduled with
+/// [`Transport::run_after`] is only queued, as there's no run loop to wait
+/// on; call [`Replay::run_delayed`], or wrap this in a transport that
+/// schedules it on the platform's run loop.
+///
+/// Each request gets the first recorded response to the same method and
+/// path that hasn't been used yet, or the last one if they all have.
+#[derive(Default)]
+pub struct Replay {
+    exchanges: Vec<Exchange>,
+    used: RefCell<Vec<bool>>,
+    delayed: RefCell<Vec<Delayed>>,
+}
+
+impl fmt::Debug for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Replay")
+            .field("exchanges", &self.exchanges)
+            .field("used", &self.used)
+            .field("delayed", &self.delayed.borrow().len())
+            .finish()
+    }
+}
+
+impl Replay {
+    pub fn new(exchanges: Vec<Exchange>) -> Self {
+        Self {
+            used: RefCell::new(vec![false; exchanges.len()]),
+            exchanges,
+            delayed: RefCell::default(),
+        }
+    }
+
+    /// Load a fixture file written by [`Recorder`].
+    pub fn load(path: &Path) -> io::Result<Self> {
+        parse_fixture(&fs::read_to_string(path)?).map(Self::new)
+    }
+
+    fn response(&self, request: &Request) -> Option<Response> {
+        let mut used = self.used.borrow_mut();
+        let mut last = None;
+        for (i, exchange) in self.exchanges.iter().enumerate() {
+            if !exchange.matches(request) {
+                continue;
+            }
+            if !used[i] {
+                used[i] = true;
+                return Some(exchange.to_response());
+            }
+            last = Some(exchange);
+        }
+        last.map(Exchange::to_response)
+    }
+
+    /// Run the work queued by [`Transport::run_after`] in the order it's
+    /// due, blocking the calling thread until then, and until no more is
+    /// queued.
+    pub fn run_delayed(&self) {
+        loop {
+            let next = {
+                let mut delayed = self.delayed.borrow_mut();
+                let Some(i) = (0..delayed.len()).min_by_key(|i| delayed[*i].0) else {
+                    return;
+                };
+                delayed.remove(i)
+            };
+            let (due, f) = next;
+            thread::sleep(due.saturating_duration_since(Instant::now()));
+            f();
+        }
+    }
+}
+
+impl Transport for Replay {
+    fn send(&self, request: Request, completion_handler: Box<dyn FnOnce(Result<Response, Error>)>) {
+        let res = self.response(&request).ok_or_else(|| Error::Transport {
+            description: format!(
+                "no recorded response to {} {}",
+                request.method.as_str(),
+                redact_path(&request.url)
+            ),
+            url: request.url,
+        });
+        completion_handler(res)
+    }
+
+    /// Only queues `f`, see [`Replay::run_delayed`].
+    fn run_after(&self, delay: Duration, f: Box<dyn FnOnce()>) {
+        self.delayed.borrow_mut().push((Instant::now() + delay, f));
+    }
+}
+
+/// Parse the contents of a fixture file, ignoring empty lines.
+pub fn parse_fixture(s: &str) -> io::Result<Vec<Exchange>> {
+    s.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|err| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {err}", i + 1))
+            })
+        })
+        .collect()
+}
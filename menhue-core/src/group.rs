@@ -0,0 +1,44 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::light::LightState;
+use crate::resource::parse_resources;
+
+/// A group of lights, as returned from `GET /api/<username>/groups/<id>`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Group {
+    /// The key in the `/groups` dictionary.
+    #[serde(skip)]
+    pub id: String,
+    /// The key of the bridge this belongs to in a
+    /// [`Registry`](crate::registry::Registry), or empty.
+    #[serde(skip)]
+    pub bridge: String,
+    pub name: String,
+    /// The ids of the lights in the group.
+    #[serde(default)]
+    pub lights: Vec<String>,
+    /// E.g. "Room", "Zone" or "LightGroup".
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    /// The kind of room, e.g. "Living room".
+    pub class: Option<String>,
+    #[serde(default)]
+    pub state: GroupState,
+    /// The last state sent to the group.
+    #[serde(default)]
+    pub action: LightState,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct GroupState {
+    #[serde(default)]
+    pub all_on: bool,
+    #[serde(default)]
+    pub any_on: bool,
+}
+
+/// Parse the response from `GET /api/<username>/groups`.
+pub fn parse_groups(json: &Value) -> Result<Vec<Group>, serde_json::Error> {
+    parse_resources(json, |group: &mut Group, id| group.id = id)
+}
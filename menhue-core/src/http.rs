@@ -0,0 +1,234 @@
+//! A minimal blocking HTTP/1.1 client.
+//!
+//! Used where there's no platform HTTP stack to leave the sending to, such
+//! as discovery, and as the [`Transport`](crate::transport::Transport) in
+//! tests and command line tools.
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rustls::pki_types::ServerName;
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+
+use crate::api::{Error, Request};
+use crate::transport::Response;
+
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    timeout: Duration,
+    tls: Arc<ClientConfig>,
+}
+
+impl HttpClient {
+    /// A client that trusts the usual public CAs for HTTPS.
+    pub fn new(timeout: Duration) -> Self {
+        let roots = RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        let tls =
+            ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .expect("ring supports the default protocol versions")
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+        Self::with_tls_config(timeout, tls)
+    }
+
+    /// A client with a custom TLS configuration, such as
+    /// [`TrustPolicy::client_config`](crate::tls::TrustPolicy::client_config)
+    /// for talking to a bridge.
+    pub fn with_tls_config(timeout: Duration, tls: ClientConfig) -> Self {
+        Self {
+            timeout,
+            tls: Arc::new(tls),
+        }
+    }
+
+    /// Send a request, and wait for the whole response.
+    pub fn send(&self, request: &Request) -> Result<Response, Error> {
+        self.try_send(request).map_err(|err| Error::Transport {
+            url: request.url.clone(),
+            description: err.to_string(),
+        })
+    }
+
+    fn try_send(&self, request: &Request) -> io::Result<Response> {
+        let url = Url::parse(&request.url)?;
+        let addr = (url.host, url.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| invalid_input("host has no addresses"))?;
+        let stream = TcpStream::connect_timeout(&addr, self.timeout)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        if url.https {
+            let server_name = ServerName::try_from(url.host.to_string())
+                .map_err(|err| invalid_input(&err.to_string()))?;
+            let connection = ClientConnection::new(self.tls.clone(), server_name)
+                .map_err(|err| io::Error::other(err.to_string()))?;
+            exchange(StreamOwned::new(connection, stream), request, &url)
+        } else {
+            exchange(stream, request, &url)
+        }
+    }
+}
+
+struct Url<'a> {
+    https: bool,
+    host: &'a str,
+    port: u16,
+    /// Including the query.
+    path: &'a str,
+    /// The `Host` header.
+    authority: &'a str,
+}
+
+impl<'a> Url<'a> {
+    fn parse(url: &'a str) -> io::Result<Self> {
+        let (https, rest) = if let Some(rest) = url.strip_prefix("https://") {
+            (true, rest)
+        } else if let Some(rest) = url.strip_prefix("http://") {
+            (false, rest)
+        } else {
+            return Err(invalid_input("unsupported URL scheme"));
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(i) => rest.split_at(i),
+            None => (rest, "/"),
+        };
+
+        // `[::1]:80`, `[::1]`, `host:80` or `host`
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if !port.ends_with(']') => {
+                let port = port.parse().map_err(|_| invalid_input("invalid port"))?;
+                (host, port)
+            }
+            _ => (authority, if https { 443 } else { 80 }),
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+
+        Ok(Self {
+            https,
+            host,
+            port,
+            path,
+            authority,
+        })
+    }
+}
+
+fn exchange(
+    mut stream: impl Read + Write,
+    request: &Request,
+    url: &Url<'_>,
+) -> io::Result<Response> {
+    let mut head = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n",
+        request.method, url.path, url.authority
+    );
+    for (field, value) in &request.headers {
+        head.push_str(&format!("{field}: {value}\r\n"));
+    }
+    let body = request.body.as_deref().unwrap_or_default();
+    if request.body.is_some() {
+        head.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body)?;
+    stream.flush()?;
+
+    read_response(BufReader::new(stream))
+}
+
+/// Read a response to a request sent with `Connection: close`.
+pub(crate) fn read_response(mut reader: impl BufRead) -> io::Result<Response> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let status_code = line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| invalid_data("invalid status line"))?;
+
+    let headers = read_headers(&mut reader)?;
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    let mut body = Vec::new();
+    if header("transfer-encoding").is_some_and(|value| value.eq_ignore_ascii_case("chunked")) {
+        read_chunked(&mut reader, &mut body)?;
+    } else if let Some(len) = header("content-length") {
+        let len: u64 = len
+            .parse()
+            .map_err(|_| invalid_data("invalid content length"))?;
+        reader.take(len).read_to_end(&mut body)?;
+    } else {
+        read_to_end_lenient(&mut reader, &mut body)?;
+    }
+
+    Ok(Response {
+        status_code,
+        headers,
+        body,
+    })
+}
+
+/// Read header lines until the empty line.
+pub(crate) fn read_headers(reader: &mut impl BufRead) -> io::Result<Vec<(String, String)>> {
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(invalid_data("unexpected end of headers"));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            return Ok(headers);
+        }
+        if let Some((field, value)) = line.split_once(':') {
+            headers.push((field.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+}
+
+fn read_chunked(reader: &mut impl BufRead, body: &mut Vec<u8>) -> io::Result<()> {
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let size = line.trim().split(';').next().unwrap_or_default();
+        let size = usize::from_str_radix(size, 16).map_err(|_| invalid_data("invalid chunk"))?;
+        if size == 0 {
+            // Skip trailers
+            read_headers(reader)?;
+            return Ok(());
+        }
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader.read_exact(&mut body[start..])?;
+        reader.read_line(&mut line)?;
+    }
+}
+
+/// Some servers close TLS connections without a `close_notify`; the body is
+/// still complete in that case.
+fn read_to_end_lenient(reader: &mut impl Read, body: &mut Vec<u8>) -> io::Result<()> {
+    match reader.read_to_end(body) {
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(()),
+        res => res.map(drop),
+    }
+}
+
+fn invalid_input(description: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, description.to_string())
+}
+
+fn invalid_data(description: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, description.to_string())
+}
@@ -0,0 +1,38 @@
+//! Platform-independent parts of menhue.
+//!
+//! This contains everything that doesn't need AppKit or Foundation: building
+//! requests for the Hue bridge and interpreting its responses, the light,
+//! group and scene model, the menu state machine and the user's settings.
+//!
+//! Actually sending the requests is left to the front-end, see
+//! [`transport::Transport`].
+#![deny(unsafe_op_in_unsafe_fn)]
+
+pub mod api;
+pub mod cache;
+pub mod color;
+pub mod config;
+pub mod discovery;
+pub mod emulator;
+pub mod error;
+pub mod eventstream;
+pub mod fixture;
+pub mod group;
+pub mod http;
+pub mod light;
+pub mod pairing;
+pub mod queue;
+pub mod ratelimit;
+pub mod registry;
+mod resource;
+pub mod retry;
+pub mod scene;
+pub mod session;
+pub mod settings;
+pub mod state;
+pub mod swupdate;
+pub mod tls;
+pub mod trace;
+pub mod transport;
+pub mod v2;
+pub mod whitelist;
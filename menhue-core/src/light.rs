@@ -0,0 +1,304 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::color::Gamut;
+use crate::resource::parse_resources;
+use crate::swupdate::DeviceUpdate;
+
+/// A light, as returned from `GET /api/<username>/lights/<id>`.
+///
+/// Only `name` and `state` are required; lights differ wildly in what they
+/// support, so everything else is optional.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Light {
+    /// The key in the `/lights` dictionary.
+    #[serde(skip)]
+    pub id: String,
+    /// The key of the bridge this belongs to in a
+    /// [`Registry`](crate::registry::Registry), or empty.
+    #[serde(skip)]
+    pub bridge: String,
+    pub name: String,
+    /// E.g. "Extended color light" or "On/Off plug-in unit".
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub modelid: Option<String>,
+    pub manufacturername: Option<String>,
+    pub productname: Option<String>,
+    pub uniqueid: Option<String>,
+    pub swversion: Option<String>,
+    pub state: LightState,
+    #[serde(default)]
+    pub capabilities: LightCapabilities,
+    #[serde(default)]
+    pub config: LightConfig,
+    /// Not reported by lights that can't be updated.
+    pub swupdate: Option<DeviceUpdate>,
+}
+
+impl Light {
+    pub fn is_reachable(&self) -> bool {
+        self.state.is_reachable()
+    }
+
+    /// Whether the light can be dimmed.
+    pub fn has_brightness(&self) -> bool {
+        self.state.bri.is_some()
+    }
+
+    /// Whether the light supports setting a color temperature.
+    pub fn has_color_temperature(&self) -> bool {
+        self.state.ct.is_some() || self.capabilities.control.ct.is_some()
+    }
+
+    /// Whether the light supports setting a color.
+    pub fn has_color(&self) -> bool {
+        self.state.xy.is_some() || self.capabilities.control.colorgamuttype.is_some()
+    }
+}
+
+/// The current state of a light.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct LightState {
+    pub on: Option<bool>,
+    /// Brightness, from 1 to 254.
+    pub bri: Option<u8>,
+    /// Hue, from 0 to 65535.
+    pub hue: Option<u16>,
+    /// Saturation, from 0 to 254.
+    pub sat: Option<u8>,
+    /// CIE xy color coordinates.
+    pub xy: Option<[f64; 2]>,
+    /// Color temperature in mireds.
+    pub ct: Option<u16>,
+    pub alert: Option<String>,
+    pub effect: Option<String>,
+    pub colormode: Option<ColorMode>,
+    /// "homeautomation" or "streaming".
+    pub mode: Option<String>,
+    /// Not reported by some lights, in which case we assume they are.
+    pub reachable: Option<bool>,
+}
+
+impl LightState {
+    pub fn is_reachable(&self) -> bool {
+        self.reachable.unwrap_or(true)
+    }
+
+    pub fn is_on(&self) -> bool {
+        self.on.unwrap_or(false)
+    }
+}
+
+/// Which of the color attributes were last used to set the color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    Hs,
+    Xy,
+    Ct,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct LightCapabilities {
+    #[serde(default)]
+    pub certified: bool,
+    #[serde(default)]
+    pub control: LightControl,
+    pub streaming: Option<LightStreaming>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct LightControl {
+    /// Minimum dim level, in 1/100th percent.
+    pub mindimlevel: Option<u32>,
+    pub maxlumen: Option<u32>,
+    pub colorgamuttype: Option<GamutType>,
+    /// The red, green and blue corners of the color gamut.
+    pub colorgamut: Option<[[f64; 2]; 3]>,
+    pub ct: Option<CtRange>,
+}
+
+/// The color gamuts used by different generations of Hue lights.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum GamutType {
+    A,
+    B,
+    C,
+    /// Any light not made by Philips.
+    #[serde(other)]
+    Other,
+}
+
+/// The supported color temperature range, in mireds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct CtRange {
+    pub min: u16,
+    pub max: u16,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct LightStreaming {
+    #[serde(default)]
+    pub renderer: bool,
+    #[serde(default)]
+    pub proxy: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct LightConfig {
+    pub archetype: Option<String>,
+    pub function: Option<String>,
+    pub direction: Option<String>,
+}
+
+/// Parse the response from `GET /api/<username>/lights`.
+///
+/// The lights are sorted by their id. Lights that don't parse are skipped.
+pub fn parse_lights(json: &Value) -> Result<Vec<Light>, serde_json::Error> {
+    parse_resources(json, |light: &mut Light, id| light.id = id)
+}
+
+/// A change to the state of a light, as sent to
+/// `PUT /api/<username>/lights/<id>/state`.
+///
+/// The color is set with either `xy`, `ct`, or `hue` and `sat`. The bridge
+/// ignores the others when several are set, so see [`Self::validate`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StateUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bri: Option<u8>,
+    /// Hue, from 0 to 65535.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hue: Option<u16>,
+    /// Saturation, from 0 to 254.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sat: Option<u8>,
+    /// CIE xy color coordinates.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub xy: Option<[f64; 2]>,
+    /// Color temperature in mireds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ct: Option<u16>,
+    /// In multiples of 100ms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitiontime: Option<u16>,
+}
+
+impl StateUpdate {
+    /// The color mode the light will be in after the update, if it sets a
+    /// color.
+    pub fn colormode(&self) -> Option<ColorMode> {
+        if self.xy.is_some() {
+            Some(ColorMode::Xy)
+        } else if self.ct.is_some() {
+            Some(ColorMode::Ct)
+        } else if self.hue.is_some() || self.sat.is_some() {
+            Some(ColorMode::Hs)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the update sets a color.
+    pub fn sets_color(&self) -> bool {
+        self.colormode().is_some()
+    }
+
+    /// Check the update against what `light` supports.
+    ///
+    /// Lights that don't report their color temperature range or gamut are
+    /// checked against the ranges the bridge accepts.
+    pub fn validate(&self, light: &Light) -> Result<(), InvalidUpdate> {
+        let control = &light.capabilities.control;
+        let modes = [
+            self.xy.is_some(),
+            self.ct.is_some(),
+            self.hue.is_some() || self.sat.is_some(),
+        ];
+        if modes.iter().filter(|set| **set).count() > 1 {
+            return Err(InvalidUpdate::ConflictingColorModes);
+        }
+
+        if let Some(bri) = self.bri {
+            if !light.has_brightness() {
+                return Err(InvalidUpdate::Unsupported { attribute: "bri" });
+            }
+            if bri == 0 || bri == 255 {
+                return Err(InvalidUpdate::OutOfRange { attribute: "bri" });
+            }
+        }
+        if let Some(ct) = self.ct {
+            if !light.has_color_temperature() {
+                return Err(InvalidUpdate::Unsupported { attribute: "ct" });
+            }
+            let range = control.ct.unwrap_or(CtRange { min: 153, max: 500 });
+            if !range.contains(ct) {
+                return Err(InvalidUpdate::OutOfRange { attribute: "ct" });
+            }
+        }
+        if let Some(xy) = self.xy {
+            if !light.has_color() {
+                return Err(InvalidUpdate::Unsupported { attribute: "xy" });
+            }
+            if !Gamut::of_light(light).contains(xy) {
+                return Err(InvalidUpdate::OutOfRange { attribute: "xy" });
+            }
+        }
+        for (attribute, set) in [("hue", self.hue.is_some()), ("sat", self.sat.is_some())] {
+            if set && !light.has_color() {
+                return Err(InvalidUpdate::Unsupported { attribute });
+            }
+        }
+        if self.sat == Some(255) {
+            return Err(InvalidUpdate::OutOfRange { attribute: "sat" });
+        }
+        Ok(())
+    }
+}
+
+impl CtRange {
+    pub fn contains(self, ct: u16) -> bool {
+        (self.min..=self.max).contains(&ct)
+    }
+
+    /// Limit `ct` to the range.
+    pub fn clamp(self, ct: u16) -> u16 {
+        ct.clamp(self.min, self.max.max(self.min))
+    }
+}
+
+/// Why a [`StateUpdate`] can't be applied to a light.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidUpdate {
+    /// The light doesn't have the attribute, e.g. `ct` for a color-only
+    /// light.
+    Unsupported { attribute: &'static str },
+    /// Beyond the light's color temperature range or gamut, or the range
+    /// the bridge accepts.
+    OutOfRange { attribute: &'static str },
+    /// More than one of `xy`, `ct` and `hue`/`sat` is set.
+    ConflictingColorModes,
+}
+
+impl fmt::Display for InvalidUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported { attribute } => {
+                write!(f, "the light does not support setting {attribute}")
+            }
+            Self::OutOfRange { attribute } => {
+                write!(f, "{attribute} is out of the light's range")
+            }
+            Self::ConflictingColorModes => {
+                write!(f, "only one of xy, ct and hue/sat may be set")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvalidUpdate {}
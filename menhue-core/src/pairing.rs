@@ -0,0 +1,140 @@
+//! Creating a user on the bridge, which only works shortly after its link
+//! button has been pressed.
+//!
+//! Send [`Client::connect_request`] and pass the outcome to
+//! [`Pairing::handle`], which says whether to try again.
+//!
+//! See <https://developers.meethue.com/develop/hue-api/7-configuration-api/#71_create_user>.
+//!
+//! [`Client::connect_request`]: crate::api::Client::connect_request
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::api::Error;
+use crate::error::HueErrorKind;
+
+/// The details returned by the bridge when pairing succeeds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    /// The username, also known as the application key.
+    pub username: String,
+    /// The pre-shared key for entertainment streaming, in hex.
+    ///
+    /// Only returned by bridges that support entertainment, and only when
+    /// asked for, which [`Client::connect_request`] does.
+    ///
+    /// [`Client::connect_request`]: crate::api::Client::connect_request
+    pub clientkey: Option<String>,
+}
+
+/// The `devicetype` that identifies us in the bridge's list of users, e.g.
+/// `menhue#MacBook-Pro`.
+pub fn devicetype(hostname: &str) -> String {
+    let hostname = hostname.strip_suffix(".local").unwrap_or(hostname);
+    // The bridge allows at most 19 characters after the `#`
+    let device: String = hostname
+        .chars()
+        .filter(|c| !c.is_control() && *c != '#')
+        .take(19)
+        .collect();
+    if device.is_empty() {
+        "menhue".to_string()
+    } else {
+        format!("menhue#{device}")
+    }
+}
+
+#[derive(Debug)]
+pub enum PairingError {
+    /// The link button wasn't pressed in time.
+    TimedOut,
+    /// The request failed for some other reason than the link button.
+    Api(Error),
+}
+
+impl fmt::Display for PairingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TimedOut => write!(f, "the link button on the bridge was not pressed"),
+            Self::Api(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for PairingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::TimedOut => None,
+            Self::Api(err) => Some(err),
+        }
+    }
+}
+
+impl From<Error> for PairingError {
+    fn from(err: Error) -> Self {
+        Self::Api(err)
+    }
+}
+
+/// What to do after an attempt.
+#[derive(Debug)]
+pub enum Step {
+    /// The link button hasn't been pressed yet; send the request again
+    /// after `after`. The user has `remaining` left to press it.
+    Retry {
+        after: Duration,
+        remaining: Duration,
+    },
+    /// Pairing is over, successfully or not.
+    Done(Result<Credentials, PairingError>),
+}
+
+/// Repeated attempts at creating a user, until the link button is pressed
+/// or the deadline has passed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pairing {
+    deadline: Instant,
+    interval: Duration,
+}
+
+impl Pairing {
+    /// How long the user has to go press the button.
+    pub const TIMEOUT: Duration = Duration::from_secs(60);
+    /// How often to ask the bridge.
+    pub const INTERVAL: Duration = Duration::from_secs(2);
+
+    /// Start pairing at `now`.
+    pub fn new(now: Instant) -> Self {
+        Self::with_timing(now, Self::TIMEOUT, Self::INTERVAL)
+    }
+
+    pub fn with_timing(now: Instant, timeout: Duration, interval: Duration) -> Self {
+        Self {
+            deadline: now + timeout,
+            interval,
+        }
+    }
+
+    /// Decide what to do with the outcome of an attempt that finished at
+    /// `now`.
+    pub fn handle(&self, now: Instant, res: Result<Credentials, Error>) -> Step {
+        match res {
+            Ok(credentials) => Step::Done(Ok(credentials)),
+            Err(err)
+                if err.hue_error().map(|error| error.kind)
+                    == Some(HueErrorKind::LinkButtonNotPressed) =>
+            {
+                let remaining = self.deadline.saturating_duration_since(now);
+                if remaining.is_zero() {
+                    Step::Done(Err(PairingError::TimedOut))
+                } else {
+                    Step::Retry {
+                        after: self.interval.min(remaining),
+                        remaining,
+                    }
+                }
+            }
+            Err(err) => Step::Done(Err(PairingError::Api(err))),
+        }
+    }
+}
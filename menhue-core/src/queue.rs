@@ -0,0 +1,112 @@
+//! Sending only the newest change to each resource.
+//!
+//! Dragging a slider produces changes faster than the bridge applies them,
+//! and sending each of them would build a backlog that replays stale values
+//! long after the drag ended. Instead, at most one request per resource is
+//! in flight, and the changes made meanwhile are merged into a single
+//! pending one, which is sent once the request finishes.
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
+
+use crate::light::StateUpdate;
+
+/// A command that can be replaced by a newer one.
+pub trait Supersede {
+    /// Combine with a newer command, which takes precedence.
+    fn supersede(self, newer: Self) -> Self;
+}
+
+impl Supersede for StateUpdate {
+    fn supersede(self, newer: Self) -> Self {
+        // The bridge prefers xy over ct over hue/sat, so an older color
+        // would win over a newer one in another mode
+        let older = if newer.colormode().is_none() || newer.colormode() == self.colormode() {
+            self.clone()
+        } else {
+            Self::default()
+        };
+        Self {
+            on: newer.on.or(self.on),
+            bri: newer.bri.or(self.bri),
+            hue: newer.hue.or(older.hue),
+            sat: newer.sat.or(older.sat),
+            xy: newer.xy.or(older.xy),
+            ct: newer.ct.or(older.ct),
+            transitiontime: newer.transitiontime.or(self.transitiontime),
+        }
+    }
+}
+
+/// Commands per resource, keyed by e.g. the light id.
+///
+/// Cheap to clone, and clones share the same queue.
+pub struct CommandQueue<T> {
+    /// The pending command of each resource with a request in flight.
+    slots: Rc<RefCell<HashMap<String, Option<T>>>>,
+}
+
+impl<T> Clone for CommandQueue<T> {
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+        }
+    }
+}
+
+impl<T> Default for CommandQueue<T> {
+    fn default() -> Self {
+        Self {
+            slots: Rc::default(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for CommandQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let slots = self.slots.borrow();
+        f.debug_map()
+            .entries(slots.iter().map(|(key, pending)| (key, pending.is_some())))
+            .finish()
+    }
+}
+
+impl<T: Supersede> CommandQueue<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a command for `key`.
+    ///
+    /// Returns the command if it should be sent now, because nothing is in
+    /// flight for `key`. Otherwise it's merged into the pending command,
+    /// and the caller must wait for [`CommandQueue::finish`].
+    pub fn push(&self, key: &str, command: T) -> Option<T> {
+        let mut slots = self.slots.borrow_mut();
+        let Some(pending) = slots.get_mut(key) else {
+            slots.insert(key.to_string(), None);
+            return Some(command);
+        };
+        *pending = Some(match pending.take() {
+            Some(pending) => pending.supersede(command),
+            None => command,
+        });
+        None
+    }
+
+    /// Call when the request for `key` has finished, whether it succeeded
+    /// or not.
+    ///
+    /// Returns the pending command, which must be sent next.
+    pub fn finish(&self, key: &str) -> Option<T> {
+        let mut slots = self.slots.borrow_mut();
+        let pending = slots.get_mut(key)?.take();
+        if pending.is_none() {
+            slots.remove(key);
+        }
+        pending
+    }
+
+    /// Whether a request for `key` is in flight.
+    pub fn is_busy(&self, key: &str) -> bool {
+        self.slots.borrow().contains_key(key)
+    }
+}
@@ -0,0 +1,212 @@
+//! Keeping below the rate of commands that the bridge can handle.
+//!
+//! The bridge forwards light and group commands to the Zigbee network,
+//! which starts dropping messages at around 10 light commands or 1 group
+//! command per second. Requests beyond that are delayed, never dropped.
+//!
+//! See <https://developers.meethue.com/develop/application-design-guidance/hue-system-performance/>.
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use crate::api::{Method, Request};
+
+/// The kinds of traffic that are limited separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Bucket {
+    /// Changes to a single light.
+    Light,
+    /// Changes to a group, which the bridge broadcasts.
+    Group,
+    /// Requests that don't reach the Zigbee network: reads, and changes to
+    /// the bridge itself, like its configuration or pairing.
+    Read,
+}
+
+impl Bucket {
+    const ALL: [Self; 3] = [Self::Light, Self::Group, Self::Read];
+
+    /// The bucket that a request counts against.
+    pub fn for_request(request: &Request) -> Self {
+        let url = &request.url;
+        if request.method == Method::Get {
+            Self::Read
+        } else if ["/groups/", "/grouped_light/", "/resource/scene/"]
+            .iter()
+            .any(|path| url.contains(path))
+        {
+            // Recalling a scene is broadcast like a group command
+            Self::Group
+        } else if url.contains("/lights/") || url.contains("/resource/light/") {
+            Self::Light
+        } else {
+            Self::Read
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Self::Light => 0,
+            Self::Group => 1,
+            Self::Read => 2,
+        }
+    }
+}
+
+/// How many requests may be made.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limit {
+    /// The sustained rate.
+    pub per_second: f64,
+    /// How many requests may be made at once after a quiet period.
+    pub burst: f64,
+}
+
+impl Limit {
+    pub const LIGHT: Self = Self {
+        per_second: 10.0,
+        burst: 1.0,
+    };
+    pub const GROUP: Self = Self {
+        per_second: 1.0,
+        burst: 1.0,
+    };
+    pub const READ: Self = Self {
+        per_second: 10.0,
+        burst: 5.0,
+    };
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    limit: Limit,
+    tokens: f64,
+    updated: Option<Instant>,
+}
+
+impl TokenBucket {
+    fn new(limit: Limit) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst,
+            updated: None,
+        }
+    }
+
+    /// Take a token, or return how long until one is available.
+    fn take(&mut self, now: Instant) -> Result<(), Duration> {
+        if let Some(updated) = self.updated {
+            let elapsed = now.saturating_duration_since(updated).as_secs_f64();
+            self.tokens = (self.tokens + elapsed * self.limit.per_second).min(self.limit.burst);
+        }
+        self.updated = Some(now);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.limit.per_second,
+            ))
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Inner<T> {
+    buckets: [(TokenBucket, VecDeque<T>); 3],
+}
+
+/// The outcome of [`RateLimiter::poll`].
+#[derive(Debug)]
+pub struct Ready<T> {
+    /// The items that may be sent now, in the order they were pushed.
+    pub items: Vec<T>,
+    /// When to poll again, if items are still waiting.
+    pub next_poll: Option<Instant>,
+}
+
+/// Queues of requests (or anything standing in for them), released at the
+/// rate of their [`Bucket`].
+///
+/// The time is passed in, so it's up to the caller to wait. Cheap to clone,
+/// and clones share the same queues.
+#[derive(Debug)]
+pub struct RateLimiter<T> {
+    inner: Rc<RefCell<Inner<T>>>,
+}
+
+impl<T> Clone for RateLimiter<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Default for RateLimiter<T> {
+    fn default() -> Self {
+        Self::with_limits(Limit::LIGHT, Limit::GROUP, Limit::READ)
+    }
+}
+
+impl<T> RateLimiter<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_limits(light: Limit, group: Limit, read: Limit) -> Self {
+        let bucket = |limit| (TokenBucket::new(limit), VecDeque::new());
+        Self {
+            inner: Rc::new(RefCell::new(Inner {
+                buckets: [bucket(light), bucket(group), bucket(read)],
+            })),
+        }
+    }
+
+    /// Queue an item; call [`RateLimiter::poll`] afterwards to see if it
+    /// can be sent right away.
+    pub fn push(&self, bucket: Bucket, item: T) {
+        self.inner.borrow_mut().buckets[bucket.index()]
+            .1
+            .push_back(item);
+    }
+
+    /// Take the items that may be sent at `now`.
+    pub fn poll(&self, now: Instant) -> Ready<T> {
+        let mut inner = self.inner.borrow_mut();
+        let mut ready = Ready {
+            items: Vec::new(),
+            next_poll: None,
+        };
+        for (tokens, queue) in &mut inner.buckets {
+            while !queue.is_empty() {
+                match tokens.take(now) {
+                    Ok(()) => ready.items.extend(queue.pop_front()),
+                    Err(wait) => {
+                        let at = now + wait;
+                        ready.next_poll = Some(ready.next_poll.map_or(at, |next| next.min(at)));
+                        break;
+                    }
+                }
+            }
+        }
+        ready
+    }
+
+    /// How many items are waiting in `bucket`.
+    pub fn queue_depth(&self, bucket: Bucket) -> usize {
+        self.inner.borrow().buckets[bucket.index()].1.len()
+    }
+
+    /// How many items are waiting in total.
+    pub fn total_queue_depth(&self) -> usize {
+        Bucket::ALL
+            .into_iter()
+            .map(|bucket| self.queue_depth(bucket))
+            .sum()
+    }
+}
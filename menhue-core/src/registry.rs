@@ -0,0 +1,186 @@
+//! Several bridges, each with its own credentials and session.
+//!
+//! Lights, groups and scenes fetched through the registry are tagged with
+//! the key of their bridge, since their ids are only unique per bridge.
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use crate::api::Error;
+use crate::group::Group;
+use crate::light::Light;
+use crate::scene::Scene;
+use crate::session::Session;
+
+type Fetched<T> = Box<dyn FnOnce(Result<Vec<T>, Error>)>;
+
+/// A bridge in a [`Registry`].
+#[derive(Debug, Clone)]
+pub struct Bridge<S> {
+    /// The bridge id in lower case if known, otherwise the host.
+    pub key: String,
+    /// For showing to the user, e.g. the name from the bridge's config.
+    pub name: String,
+    pub session: S,
+}
+
+/// The resources of one bridge, see [`Registry::lights`].
+#[derive(Debug)]
+pub struct Section<T> {
+    pub bridge: String,
+    pub name: String,
+    pub items: Result<Vec<T>, Error>,
+}
+
+/// The bridges, in the order they were added.
+///
+/// `S` is the session type, usually a [`Session`] or something wrapping
+/// one. Cheap to clone, and clones share the same bridges.
+#[derive(Debug)]
+pub struct Registry<S> {
+    bridges: Rc<RefCell<Vec<Bridge<S>>>>,
+}
+
+impl<S> Clone for Registry<S> {
+    fn clone(&self) -> Self {
+        Self {
+            bridges: self.bridges.clone(),
+        }
+    }
+}
+
+impl<S> Default for Registry<S> {
+    fn default() -> Self {
+        Self {
+            bridges: Rc::default(),
+        }
+    }
+}
+
+impl<S: Clone> Registry<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a bridge, replacing the one with the same key, if any.
+    pub fn add(&self, bridge: Bridge<S>) {
+        let mut bridges = self.bridges.borrow_mut();
+        match bridges.iter_mut().find(|b| b.key == bridge.key) {
+            Some(existing) => *existing = bridge,
+            None => bridges.push(bridge),
+        }
+    }
+
+    pub fn remove(&self, key: &str) -> Option<Bridge<S>> {
+        let mut bridges = self.bridges.borrow_mut();
+        let i = bridges.iter().position(|b| b.key == key)?;
+        Some(bridges.remove(i))
+    }
+
+    /// Rename a bridge, e.g. once its config has been fetched.
+    pub fn set_name(&self, key: &str, name: String) {
+        if let Some(bridge) = self.bridges.borrow_mut().iter_mut().find(|b| b.key == key) {
+            bridge.name = name;
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<Bridge<S>> {
+        self.bridges.borrow().iter().find(|b| b.key == key).cloned()
+    }
+
+    /// The session of the bridge that `key` belongs to.
+    pub fn session(&self, key: &str) -> Option<S> {
+        self.get(key).map(|bridge| bridge.session)
+    }
+
+    pub fn bridges(&self) -> Vec<Bridge<S>> {
+        self.bridges.borrow().clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.bridges.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bridges.borrow().is_empty()
+    }
+}
+
+impl<S: Clone + AsRef<Session>> Registry<S> {
+    /// Fetch the lights of every bridge, and call `completion_handler` once
+    /// all of them have answered, with a section per bridge.
+    pub fn lights(&self, completion_handler: impl FnOnce(Vec<Section<Light>>) + 'static) {
+        self.fetch_all(
+            |session, done| session.lights(done),
+            |light, key| light.bridge = key.to_string(),
+            completion_handler,
+        )
+    }
+
+    /// Like [`Registry::lights`].
+    pub fn groups(&self, completion_handler: impl FnOnce(Vec<Section<Group>>) + 'static) {
+        self.fetch_all(
+            |session, done| session.groups(done),
+            |group, key| group.bridge = key.to_string(),
+            completion_handler,
+        )
+    }
+
+    /// Like [`Registry::lights`].
+    pub fn scenes(&self, completion_handler: impl FnOnce(Vec<Section<Scene>>) + 'static) {
+        self.fetch_all(
+            |session, done| session.scenes(done),
+            |scene, key| scene.bridge = key.to_string(),
+            completion_handler,
+        )
+    }
+
+    fn fetch_all<T: 'static>(
+        &self,
+        fetch: fn(&Session, Fetched<T>),
+        tag: fn(&mut T, &str),
+        completion_handler: impl FnOnce(Vec<Section<T>>) + 'static,
+    ) {
+        let bridges = self.bridges();
+        if bridges.is_empty() {
+            completion_handler(Vec::new());
+            return;
+        }
+
+        let sections: Rc<RefCell<Vec<Option<Section<T>>>>> =
+            Rc::new(RefCell::new(bridges.iter().map(|_| None).collect()));
+        let remaining = Rc::new(Cell::new(bridges.len()));
+        let completion_handler = Rc::new(Cell::new(Some(completion_handler)));
+        for (i, bridge) in bridges.into_iter().enumerate() {
+            let sections = sections.clone();
+            let remaining = remaining.clone();
+            let completion_handler = completion_handler.clone();
+            let Bridge { key, name, session } = bridge;
+            fetch(
+                session.as_ref(),
+                Box::new(move |res| {
+                    let items = res.map(|mut items| {
+                        for item in &mut items {
+                            tag(item, &key);
+                        }
+                        items
+                    });
+                    sections.borrow_mut()[i] = Some(Section {
+                        bridge: key,
+                        name,
+                        items,
+                    });
+
+                    remaining.set(remaining.get() - 1);
+                    if remaining.get() == 0 {
+                        let sections = sections.take().into_iter().flatten().collect();
+                        if let Some(completion_handler) = completion_handler.take() {
+                            completion_handler(sections);
+                        }
+                    }
+                }),
+            );
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::collections::BTreeMap;
+
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+
+/// Parse a dictionary of resources keyed by their id, as returned from e.g.
+/// `GET /api/<username>/lights`.
+///
+/// Each resource is parsed on its own, and those that don't parse are
+/// skipped, so that one odd light doesn't hide all the others. Only a
+/// response that isn't a dictionary at all is an error.
+///
+/// The resources are sorted by their id.
+pub(crate) fn parse_resources<T: DeserializeOwned>(
+    json: &Value,
+    set_id: impl Fn(&mut T, String),
+) -> Result<Vec<T>, serde_json::Error> {
+    let resources = BTreeMap::<String, Value>::deserialize(json)?;
+    let mut resources: Vec<(String, T)> = resources
+        .into_iter()
+        .filter_map(|(id, resource)| Some((id, T::deserialize(resource).ok()?)))
+        .collect();
+    // Ids are usually numbers, sort them as such
+    resources.sort_by_key(|(id, _)| (id.parse::<u64>().ok(), id.clone()));
+    Ok(resources
+        .into_iter()
+        .map(|(id, mut resource)| {
+            set_id(&mut resource, id);
+            resource
+        })
+        .collect())
+}
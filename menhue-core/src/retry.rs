@@ -0,0 +1,128 @@
+//! Retrying requests that failed for a reason that is likely to go away.
+//!
+//! Only requests that can safely be sent twice are retried: reads, and
+//! changes to an absolute state (`"bri": 100`, but not `"bri_inc": 10`).
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use serde_json::Value;
+
+use crate::api::{Error, Method, Request, UpdateResult};
+use crate::error::{HueError, HueErrorKind};
+
+/// What to do about a failed request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// Send the request again after the delay.
+    Retry { after: Duration },
+    /// Report the error.
+    GiveUp,
+}
+
+/// How often, and how soon, to retry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Including the first attempt.
+    pub max_attempts: u32,
+    /// The delay before the first retry, which doubles for each retry after
+    /// that.
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry.
+    pub const NONE: Self = Self {
+        max_attempts: 1,
+        initial_delay: Duration::ZERO,
+        max_delay: Duration::ZERO,
+    };
+
+    /// Decide whether to retry `request` after it failed with `err`.
+    ///
+    /// `attempts` is the number of attempts made so far, and `jitter` a
+    /// random number in `0.0..1.0`, see [`jitter`]; the delay is spread
+    /// between half and all of the backoff, so that several clients don't
+    /// retry in lockstep.
+    pub fn decide(&self, request: &Request, err: &Error, attempts: u32, jitter: f64) -> Decision {
+        if attempts >= self.max_attempts || !is_idempotent(request) || !is_transient(err) {
+            return Decision::GiveUp;
+        }
+        let backoff = self
+            .initial_delay
+            .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+            .min(self.max_delay);
+        Decision::Retry {
+            after: backoff.mul_f64(0.5 + jitter.clamp(0.0, 1.0) / 2.0),
+        }
+    }
+}
+
+/// Whether sending `request` twice has the same effect as sending it once.
+pub fn is_idempotent(request: &Request) -> bool {
+    match request.method {
+        Method::Get => true,
+        Method::Put => match &request.body {
+            Some(body) => serde_json::from_slice(body).is_ok_and(|json| !is_relative(&json)),
+            None => true,
+        },
+        Method::Post | Method::Delete => false,
+    }
+}
+
+/// Whether a request body changes something relative to its current state,
+/// like `bri_inc` in v1 or `dimming_delta` in v2.
+fn is_relative(json: &Value) -> bool {
+    match json {
+        Value::Object(dict) => dict.iter().any(|(key, value)| {
+            key.ends_with("_inc") || key.ends_with("delta") || is_relative(value)
+        }),
+        Value::Array(items) => items.iter().any(is_relative),
+        _ => false,
+    }
+}
+
+/// Whether `err` is likely to go away by itself.
+pub fn is_transient(err: &Error) -> bool {
+    match err {
+        // Timeouts and dropped connections
+        Error::Transport { .. } => true,
+        Error::Hue { error, .. } => error.kind == HueErrorKind::InternalError,
+        err => err.is_overloaded(),
+    }
+}
+
+/// The error to retry an update for, if the bridge accepted the request but
+/// every attribute failed transiently, e.g. with an internal error (901)
+/// while it was busy.
+pub fn transient_update_error(url: &str, result: &UpdateResult) -> Option<Error> {
+    let as_error = |error: &HueError| Error::Hue {
+        url: url.to_string(),
+        error: error.clone(),
+    };
+    let first = result.errors.first()?;
+    result
+        .errors
+        .iter()
+        .all(|error| is_transient(&as_error(error)))
+        .then(|| as_error(first))
+}
+
+/// A random number in `0.0..1.0` for [`RetryPolicy::decide`].
+pub fn jitter() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
@@ -0,0 +1,35 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::resource::parse_resources;
+
+/// A scene, as returned from `GET /api/<username>/scenes`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Scene {
+    /// The key in the `/scenes` dictionary.
+    #[serde(skip)]
+    pub id: String,
+    /// The key of the bridge this belongs to in a
+    /// [`Registry`](crate::registry::Registry), or empty.
+    #[serde(skip)]
+    pub bridge: String,
+    pub name: String,
+    /// "LightScene" or "GroupScene".
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    /// The group id, for group scenes.
+    pub group: Option<String>,
+    #[serde(default)]
+    pub lights: Vec<String>,
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub recycle: bool,
+    #[serde(default)]
+    pub locked: bool,
+    pub lastupdated: Option<String>,
+}
+
+/// Parse the response from `GET /api/<username>/scenes`.
+pub fn parse_scenes(json: &Value) -> Result<Vec<Scene>, serde_json::Error> {
+    parse_resources(json, |scene: &mut Scene, id| scene.id = id)
+}
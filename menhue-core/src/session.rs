@@ -0,0 +1,473 @@
+//! Sending requests to the bridge over a [`Transport`], and interpreting
+//! the responses.
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+
+use crate::api::{
+    parse_response, parse_update_response, Client, Error, Method, Request, UpdateResult,
+};
+use crate::config::{parse_config, BridgeConfig};
+use crate::group::{parse_groups, Group};
+use crate::light::{parse_lights, InvalidUpdate, Light, StateUpdate};
+use crate::pairing::{Credentials, Pairing, PairingError, Step};
+use crate::queue::{CommandQueue, Supersede};
+use crate::ratelimit::{Bucket, RateLimiter};
+use crate::retry::{self, Decision, RetryPolicy};
+use crate::scene::{parse_scenes, Scene};
+use crate::swupdate::{parse_software_update, SoftwareUpdate};
+use crate::transport::{Response, Transport};
+use crate::v2::{
+    self, GroupedLight, IdMap, LightUpdate, Resource, ResourceIdentifier, ResourceType,
+    UnknownResource,
+};
+use crate::whitelist::{parse_whitelist, DeleteError, Protected, User};
+
+/// A change to a light, see [`Session::update_light`].
+struct LightCommand {
+    update: StateUpdate,
+    completion_handler: Box<dyn FnOnce(Result<UpdateResult, Error>)>,
+}
+
+impl Supersede for LightCommand {
+    fn supersede(self, newer: Self) -> Self {
+        Self {
+            update: self.update.supersede(newer.update),
+            // The older handler is dropped without being called
+            completion_handler: newer.completion_handler,
+        }
+    }
+}
+
+type RetryObserver = Rc<dyn Fn(&Error, Duration)>;
+
+/// Cheap to clone, and clones share the same transport and queues.
+#[derive(Clone)]
+pub struct Session {
+    transport: Rc<dyn Transport>,
+    client: Client,
+    light_updates: CommandQueue<LightCommand>,
+    /// Requests that haven't been sent yet.
+    limiter: RateLimiter<Box<dyn FnOnce()>>,
+    /// Whether sending more requests is scheduled.
+    drain_scheduled: Rc<Cell<bool>>,
+    retry: RetryPolicy,
+    on_retry: Rc<RefCell<Option<RetryObserver>>>,
+}
+
+impl fmt::Debug for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Session")
+            .field("transport", &self.transport)
+            .field("client", &self.client)
+            .field("light_updates", &self.light_updates)
+            .field("queue_depth", &self.limiter.total_queue_depth())
+            .field("retry", &self.retry)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AsRef<Session> for Session {
+    fn as_ref(&self) -> &Session {
+        self
+    }
+}
+
+impl Session {
+    pub fn new(transport: impl Transport + 'static, client: Client) -> Self {
+        Self {
+            transport: Rc::new(transport),
+            client,
+            light_updates: CommandQueue::new(),
+            limiter: RateLimiter::new(),
+            drain_scheduled: Rc::new(Cell::new(false)),
+            retry: RetryPolicy::default(),
+            on_retry: Rc::default(),
+        }
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    pub fn transport(&self) -> &dyn Transport {
+        &*self.transport
+    }
+
+    /// Called with the error and the delay whenever a request is retried.
+    pub fn set_on_retry(&self, on_retry: impl Fn(&Error, Duration) + 'static) {
+        *self.on_retry.borrow_mut() = Some(Rc::new(on_retry));
+    }
+
+    /// How many requests in `bucket` are waiting for the rate limit.
+    pub fn queue_depth(&self, bucket: Bucket) -> usize {
+        self.limiter.queue_depth(bucket)
+    }
+
+    pub fn send(
+        &self,
+        request: Request,
+        completion_handler: impl FnOnce(Result<Value, Error>) + 'static,
+    ) {
+        self.send_with(request, parse_response, |_, _| None, completion_handler)
+    }
+
+    /// Send a request that changes several attributes, and report the
+    /// outcome of each of them.
+    ///
+    /// If every attribute failed because the bridge was busy, the request
+    /// is retried like one that failed as a whole.
+    pub fn send_update(
+        &self,
+        request: Request,
+        completion_handler: impl FnOnce(Result<UpdateResult, Error>) + 'static,
+    ) {
+        self.send_with(
+            request,
+            parse_update_response,
+            retry::transient_update_error,
+            completion_handler,
+        )
+    }
+
+    /// Change the state of a light.
+    ///
+    /// Only one request per light is in flight at a time; updates made
+    /// meanwhile are merged, and sent when it finishes. The completion
+    /// handlers of merged updates are dropped, only the newest is called.
+    ///
+    /// Updates the light doesn't support are rejected without sending
+    /// anything, and the completion handler isn't called.
+    pub fn update_light(
+        &self,
+        light: &Light,
+        update: StateUpdate,
+        completion_handler: impl FnOnce(Result<UpdateResult, Error>) + 'static,
+    ) -> Result<(), InvalidUpdate> {
+        update.validate(light)?;
+        let command = LightCommand {
+            update,
+            completion_handler: Box::new(completion_handler),
+        };
+        if let Some(command) = self.light_updates.push(&light.id, command) {
+            self.send_light_command(light.id.clone(), command);
+        }
+        Ok(())
+    }
+
+    fn send_light_command(&self, light_id: String, command: LightCommand) {
+        let request = self.client.set_light_state(&light_id, &command.update);
+        let this = self.clone();
+        self.send_update(request, move |res| {
+            (command.completion_handler)(res);
+            if let Some(next) = this.light_updates.finish(&light_id) {
+                this.send_light_command(light_id, next);
+            }
+        });
+    }
+
+    /// `transient` finds errors worth retrying for in responses that parsed
+    /// successfully.
+    fn send_with<T: 'static>(
+        &self,
+        request: Request,
+        parse: fn(&str, u16, &[u8]) -> Result<T, Error>,
+        transient: fn(&str, &T) -> Option<Error>,
+        completion_handler: impl FnOnce(Result<T, Error>) + 'static,
+    ) {
+        self.send_attempt(request, parse, transient, Box::new(completion_handler), 1)
+    }
+
+    /// Send a request once the rate limit allows it, and send it again if
+    /// the retry policy says so.
+    fn send_attempt<T: 'static>(
+        &self,
+        request: Request,
+        parse: fn(&str, u16, &[u8]) -> Result<T, Error>,
+        transient: fn(&str, &T) -> Option<Error>,
+        completion_handler: Box<dyn FnOnce(Result<T, Error>)>,
+        attempts: u32,
+    ) {
+        let bucket = Bucket::for_request(&request);
+        let this = self.clone();
+        let send = move || {
+            let transport = this.transport.clone();
+            transport.send(
+                request.clone(),
+                Box::new(move |res: Result<Response, Error>| {
+                    let res = res.and_then(|response| {
+                        parse(&request.url, response.status_code, &response.body)
+                    });
+                    let failure = match &res {
+                        Ok(value) => transient(&request.url, value),
+                        Err(_) => None,
+                    };
+                    if let Some(err) = res.as_ref().err().or(failure.as_ref()) {
+                        let decision = this.retry.decide(&request, err, attempts, retry::jitter());
+                        if let Decision::Retry { after } = decision {
+                            let on_retry = this.on_retry.borrow().clone();
+                            if let Some(on_retry) = on_retry {
+                                on_retry(err, after);
+                            }
+                            let transport = this.transport.clone();
+                            transport.run_after(
+                                after,
+                                Box::new(move || {
+                                    this.send_attempt(
+                                        request,
+                                        parse,
+                                        transient,
+                                        completion_handler,
+                                        attempts + 1,
+                                    );
+                                }),
+                            );
+                            return;
+                        }
+                    }
+                    completion_handler(res)
+                }),
+            );
+        };
+        self.limiter.push(bucket, Box::new(send));
+        self.drain();
+    }
+
+    /// Send the requests that the rate limit allows, and schedule sending
+    /// the rest.
+    fn drain(&self) {
+        let now = Instant::now();
+        let ready = self.limiter.poll(now);
+        for send in ready.items {
+            send();
+        }
+
+        let Some(next_poll) = ready.next_poll else {
+            return;
+        };
+        if self.drain_scheduled.replace(true) {
+            return;
+        }
+        let this = self.clone();
+        self.transport.run_after(
+            next_poll.saturating_duration_since(Instant::now()),
+            Box::new(move || {
+                this.drain_scheduled.set(false);
+                this.drain();
+            }),
+        );
+    }
+
+    /// Create a user on the bridge, asking again until its link button is
+    /// pressed or [`Pairing::TIMEOUT`] has passed.
+    ///
+    /// `on_waiting` is called with the time left each time the bridge says
+    /// that the button hasn't been pressed yet.
+    pub fn pair(
+        &self,
+        devicetype: String,
+        on_waiting: impl Fn(Duration) + 'static,
+        completion_handler: impl FnOnce(Result<Credentials, PairingError>) + 'static,
+    ) {
+        self.pair_attempt(
+            Pairing::new(Instant::now()),
+            devicetype,
+            Rc::new(on_waiting),
+            Box::new(completion_handler),
+        );
+    }
+
+    fn pair_attempt(
+        &self,
+        pairing: Pairing,
+        devicetype: String,
+        on_waiting: Rc<dyn Fn(Duration)>,
+        completion_handler: Box<dyn FnOnce(Result<Credentials, PairingError>)>,
+    ) {
+        let request = self.client.connect_request(&devicetype);
+        let url = request.url.clone();
+        let this = self.clone();
+        self.send(request, move |res| {
+            let res = res.and_then(|json| this.client.finish_connect(&url, &json));
+            match pairing.handle(Instant::now(), res) {
+                Step::Retry { after, remaining } => {
+                    on_waiting(remaining);
+                    let transport = this.transport.clone();
+                    transport.run_after(
+                        after,
+                        Box::new(move || {
+                            this.pair_attempt(pairing, devicetype, on_waiting, completion_handler);
+                        }),
+                    );
+                }
+                Step::Done(res) => completion_handler(res),
+            }
+        });
+    }
+
+    /// Fetch the bridge's public configuration, e.g. to check that the host
+    /// is a Hue bridge before pairing.
+    pub fn config(&self, completion_handler: impl FnOnce(Result<BridgeConfig, Error>) + 'static) {
+        let request = self.client.config_request();
+        let url = request.url.clone();
+        self.send(request, move |res| {
+            completion_handler(res.and_then(|json| {
+                parse_config(&json).map_err(|err| Error::InvalidResponse {
+                    url,
+                    description: format!("not a Hue bridge: {err}"),
+                })
+            }))
+        })
+    }
+
+    /// List the users that may access the bridge.
+    pub fn whitelist(&self, completion_handler: impl FnOnce(Result<Vec<User>, Error>) + 'static) {
+        self.fetch("/config", parse_whitelist, completion_handler)
+    }
+
+    /// Get the firmware update state of the bridge.
+    pub fn software_update(
+        &self,
+        completion_handler: impl FnOnce(Result<SoftwareUpdate, Error>) + 'static,
+    ) {
+        self.fetch("/config", parse_software_update, completion_handler)
+    }
+
+    /// Install the firmware updates that are ready, if any.
+    ///
+    /// Calls `completion_handler` with whether an install was started.
+    pub fn install_update(&self, completion_handler: impl FnOnce(Result<bool, Error>) + 'static) {
+        let this = self.clone();
+        self.software_update(move |res| match res {
+            Ok(update) if update.is_ready() => {
+                let request = this.client.install_update_request();
+                this.send(request, move |res| completion_handler(res.map(|_| true)));
+            }
+            Ok(_) => completion_handler(Ok(false)),
+            Err(err) => completion_handler(Err(err)),
+        })
+    }
+
+    /// Delete a user from the bridge, unless it's the one we're using or
+    /// one of the `protected` ones.
+    pub fn delete_user(
+        &self,
+        user: &User,
+        protected: &Protected,
+        completion_handler: impl FnOnce(Result<(), DeleteError>) + 'static,
+    ) {
+        if self.client.username().as_deref() == Some(&user.username) || protected.contains(user) {
+            return completion_handler(Err(DeleteError::Protected {
+                username: user.username.clone(),
+            }));
+        }
+        let request = self.client.delete_user_request(&user.username);
+        self.send(request, move |res| {
+            completion_handler(res.map(|_| ()).map_err(DeleteError::from))
+        })
+    }
+
+    pub fn lights(&self, completion_handler: impl FnOnce(Result<Vec<Light>, Error>) + 'static) {
+        self.fetch("/lights", parse_lights, completion_handler)
+    }
+
+    pub fn groups(&self, completion_handler: impl FnOnce(Result<Vec<Group>, Error>) + 'static) {
+        self.fetch("/groups", parse_groups, completion_handler)
+    }
+
+    pub fn scenes(&self, completion_handler: impl FnOnce(Result<Vec<Scene>, Error>) + 'static) {
+        self.fetch("/scenes", parse_scenes, completion_handler)
+    }
+
+    /// Fetch all v2 resources of type `T`.
+    ///
+    /// Only bridges that [support v2](BridgeConfig::supports_v2) serve
+    /// these, over HTTPS.
+    pub fn resources<T: Resource + 'static>(
+        &self,
+        completion_handler: impl FnOnce(Result<Vec<T>, Error>) + 'static,
+    ) {
+        let request = v2::Client::new(self.client.clone()).get_all::<T>();
+        self.send_with(
+            request,
+            v2::parse_response::<T>,
+            |_, _| None,
+            move |res| completion_handler(res.map(|response| response.data)),
+        )
+    }
+
+    /// Fetch the v2 ids of the lights and groups, for translating between
+    /// them and the v1 ones.
+    pub fn id_map(&self, completion_handler: impl FnOnce(Result<IdMap, Error>) + 'static) {
+        let this = self.clone();
+        self.resources(move |res: Result<Vec<v2::Light>, Error>| {
+            let lights = match res {
+                Ok(lights) => lights,
+                Err(err) => return completion_handler(Err(err)),
+            };
+            this.resources(move |res: Result<Vec<GroupedLight>, Error>| {
+                completion_handler(res.map(|grouped_lights| {
+                    let mut ids = IdMap::new();
+                    ids.extend(&lights);
+                    ids.extend(&grouped_lights);
+                    ids
+                }))
+            })
+        })
+    }
+
+    /// Change the v1 light `light_id` through the v2 API, which unlike v1
+    /// takes the brightness in percent and the transition in milliseconds.
+    ///
+    /// Lights that aren't in `ids` are rejected without sending anything,
+    /// and the completion handler isn't called.
+    pub fn update_light_v2(
+        &self,
+        ids: &IdMap,
+        light_id: &str,
+        update: &LightUpdate,
+        completion_handler: impl FnOnce(Result<v2::Response<ResourceIdentifier>, Error>) + 'static,
+    ) -> Result<(), UnknownResource> {
+        let rid = ids.light(light_id).ok_or_else(|| UnknownResource {
+            id_v1: format!("/lights/{light_id}"),
+        })?;
+        let identifier = ResourceIdentifier {
+            rid: rid.to_string(),
+            rtype: ResourceType::Light,
+        };
+        let request = v2::Client::new(self.client.clone()).update_light(&identifier, update);
+        self.send_with(
+            request,
+            v2::parse_response::<ResourceIdentifier>,
+            |_, _| None,
+            completion_handler,
+        );
+        Ok(())
+    }
+
+    /// Get and parse a resource of the user.
+    fn fetch<T: 'static>(
+        &self,
+        path: &str,
+        parse: fn(&Value) -> Result<T, serde_json::Error>,
+        completion_handler: impl FnOnce(Result<T, Error>) + 'static,
+    ) {
+        let request = self
+            .client
+            .request(Method::Get, &self.client.authenticated_path(path), None);
+        let url = request.url.clone();
+        self.send(request, move |res| {
+            completion_handler(res.and_then(|json| {
+                parse(&json).map_err(|err| Error::InvalidResponse {
+                    url,
+                    description: err.to_string(),
+                })
+            }))
+        })
+    }
+}
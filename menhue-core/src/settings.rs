@@ -0,0 +1,105 @@
+use std::path::PathBuf;
+
+use crate::trace::Level;
+
+/// User-configurable settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Settings {
+    /// Hostname or IP address of the bridge.
+    pub host: Option<String>,
+    /// The username (also known as the application key) used for
+    /// authenticating with the bridge.
+    pub username: Option<String>,
+    /// The key for entertainment streaming that was returned together with
+    /// the username.
+    pub clientkey: Option<String>,
+    /// The id of the bridge, used for verifying its certificate.
+    pub bridge_id: Option<String>,
+    /// Keep the lights up to date with the bridge's event stream, instead
+    /// of fetching them whenever the menu is opened.
+    pub live_updates: bool,
+    /// Also ask this discovery service for bridges, usually
+    /// [`nupnp::DEFAULT_URL`](crate::discovery::nupnp::DEFAULT_URL).
+    pub discovery_url: Option<String>,
+    /// Append the traffic with the bridge to this fixture file, see
+    /// [`fixture::Recorder`](crate::fixture::Recorder).
+    pub record_fixture: Option<PathBuf>,
+    /// Answer requests from this fixture file instead of talking to a
+    /// bridge, see [`fixture::Replay`](crate::fixture::Replay).
+    pub replay_fixture: Option<PathBuf>,
+    /// Already paired bridges to show besides the one above.
+    pub extra_bridges: Vec<BridgeSettings>,
+    /// Trace the requests to the bridges.
+    pub trace: Option<Level>,
+    /// Where to write the trace, instead of the default location.
+    pub trace_file: Option<PathBuf>,
+}
+
+/// A paired bridge, see [`Settings::extra_bridges`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BridgeSettings {
+    pub host: String,
+    /// If not given, the credentials are looked up by the bridge id.
+    pub username: Option<String>,
+    pub clientkey: Option<String>,
+    /// Used for verifying that the host is still the same bridge, and its
+    /// certificate.
+    pub bridge_id: Option<String>,
+}
+
+impl BridgeSettings {
+    /// Parse a comma-separated list of `[username[:clientkey]@]host[/bridgeid]`.
+    pub fn parse_list(s: &str) -> Vec<Self> {
+        s.split(',')
+            .filter_map(|bridge| {
+                let (credentials, bridge) = match bridge.trim().split_once('@') {
+                    Some((credentials, bridge)) => (Some(credentials), bridge),
+                    None => (None, bridge.trim()),
+                };
+                let (username, clientkey) = match credentials.map(|c| (c, c.split_once(':'))) {
+                    Some((_, Some((username, clientkey)))) => (Some(username), Some(clientkey)),
+                    Some((username, None)) => (Some(username), None),
+                    None => (None, None),
+                };
+                let (host, bridge_id) = match bridge.split_once('/') {
+                    Some((host, bridge_id)) => (host, Some(bridge_id)),
+                    None => (bridge, None),
+                };
+                let non_empty = |s: Option<&str>| s.filter(|s| !s.is_empty()).map(str::to_string);
+                Some(Self {
+                    host: non_empty(Some(host))?,
+                    username: non_empty(username),
+                    clientkey: non_empty(clientkey),
+                    bridge_id: non_empty(bridge_id),
+                })
+            })
+            .collect()
+    }
+}
+
+impl Settings {
+    /// Load the settings from the `HOST`, `USERNAME_KEY`, `CLIENT_KEY`,
+    /// `BRIDGE_ID`, `LIVE_UPDATES`, `DISCOVERY_URL`, `RECORD_FIXTURE`,
+    /// `REPLAY_FIXTURE`, `EXTRA_BRIDGES`, `TRACE` and `TRACE_FILE`
+    /// environment variables.
+    pub fn from_env() -> Self {
+        // TODO: Store this with CoreData
+        Self {
+            host: std::env::var("HOST").ok(),
+            username: std::env::var("USERNAME_KEY").ok(),
+            clientkey: std::env::var("CLIENT_KEY").ok(),
+            bridge_id: std::env::var("BRIDGE_ID").ok(),
+            live_updates: std::env::var_os("LIVE_UPDATES").is_some(),
+            discovery_url: std::env::var("DISCOVERY_URL").ok(),
+            record_fixture: std::env::var_os("RECORD_FIXTURE").map(PathBuf::from),
+            replay_fixture: std::env::var_os("REPLAY_FIXTURE").map(PathBuf::from),
+            extra_bridges: std::env::var("EXTRA_BRIDGES")
+                .map(|bridges| BridgeSettings::parse_list(&bridges))
+                .unwrap_or_default(),
+            trace: std::env::var("TRACE")
+                .ok()
+                .and_then(|level| Level::from_name(&level)),
+            trace_file: std::env::var_os("TRACE_FILE").map(PathBuf::from),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! The application's state machine, see the diagram in the README.
+use std::{cell::RefCell, rc::Rc};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum State {
+    #[default]
+    Init,
+    /// Getting data from cache...
+    DataFromCache,
+    /// Show login dialog.
+    LoginDialog,
+    /// Logging in...
+    FetchUser,
+    /// Menu ready.
+    MenuReady,
+    /// Fetching lamp status...
+    FetchLampStatus,
+    /// Show error in menu.
+    MenuOpenWithError(String),
+    /// Show lamps.
+    MenuOpen,
+    /// Editing lamp...
+    EditLamp,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    Launched,
+    Succeeded,
+    Failed(String),
+    PermissionDenied,
+    /// Log in with details.
+    LogIn,
+    Opened,
+    Retry,
+    Closed,
+    EditLamp,
+}
+
+impl State {
+    /// The state after the given event, or `None` if the event is not valid
+    /// in the current state.
+    pub fn next(&self, event: Event) -> Option<Self> {
+        use Event as E;
+        use State as S;
+
+        Some(match (self, event) {
+            (S::Init, E::Launched) => S::DataFromCache,
+
+            (S::DataFromCache, E::Failed(_)) => S::LoginDialog,
+            (S::DataFromCache, E::Succeeded) => S::MenuReady,
+
+            (S::LoginDialog, E::LogIn) => S::FetchUser,
+
+            (S::FetchUser, E::Failed(_) | E::PermissionDenied) => S::LoginDialog,
+            (S::FetchUser, E::Succeeded) => S::MenuReady,
+
+            (S::MenuReady, E::Opened) => S::FetchLampStatus,
+
+            (S::FetchLampStatus | S::EditLamp, E::PermissionDenied) => S::LoginDialog,
+            (S::FetchLampStatus | S::EditLamp, E::Failed(err)) => S::MenuOpenWithError(err),
+            (S::FetchLampStatus | S::EditLamp, E::Succeeded) => S::MenuOpen,
+
+            (S::MenuOpenWithError(_), E::Retry) => S::FetchLampStatus,
+
+            (S::MenuOpen, E::EditLamp) => S::EditLamp,
+
+            // The menu may be closed while a request is still in flight
+            (
+                S::FetchLampStatus | S::MenuOpenWithError(_) | S::MenuOpen | S::EditLamp,
+                E::Closed,
+            ) => S::MenuReady,
+
+            _ => return None,
+        })
+    }
+
+    /// Whether to show a loading indicator.
+    pub fn is_loading(&self) -> bool {
+        matches!(
+            self,
+            Self::DataFromCache | Self::FetchUser | Self::FetchLampStatus
+        )
+    }
+}
+
+/// Shared handle to the current [`State`].
+#[derive(Debug, Clone, Default)]
+pub struct StateMachine {
+    state: Rc<RefCell<State>>,
+}
+
+impl StateMachine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self) -> State {
+        self.state.borrow().clone()
+    }
+
+    /// Transition to the next state.
+    ///
+    /// Returns `false` and leaves the state unchanged if the event was not
+    /// valid in the current state.
+    pub fn handle(&self, event: Event) -> bool {
+        let mut state = self.state.borrow_mut();
+        match state.next(event) {
+            Some(next) => {
+                *state = next;
+                true
+            }
+            None => false,
+        }
+    }
+}
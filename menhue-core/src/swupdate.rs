@@ -0,0 +1,98 @@
+//! Firmware updates of the bridge and the devices connected to it.
+//!
+//! The bridge downloads updates by itself, and reports them in `swupdate2`
+//! of its configuration. Updates that are ready are installed during the
+//! auto-install window, or right away when asked to.
+use serde::Deserialize;
+use serde_json::Value;
+
+/// The update state of the bridge, a device, or all of them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateState {
+    NoUpdates,
+    /// Downloading the update.
+    Transferring,
+    ReadyToInstall,
+    Installing,
+    /// Not made by Philips, or too old to be updated.
+    NotUpdatable,
+    /// Only as the overall state, when some devices are ready.
+    AnyReadyToInstall,
+    /// Only as the overall state, when every device is ready.
+    AllReadyToInstall,
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+impl UpdateState {
+    /// Whether an update can be installed now.
+    pub fn is_ready(self) -> bool {
+        matches!(
+            self,
+            Self::ReadyToInstall | Self::AnyReadyToInstall | Self::AllReadyToInstall
+        )
+    }
+}
+
+/// The update state of a device, as in `swupdate` of a light.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct DeviceUpdate {
+    #[serde(default)]
+    pub state: UpdateState,
+    /// In the format of [`format_timestamp`](crate::config::format_timestamp),
+    /// or "none".
+    pub lastinstall: Option<String>,
+}
+
+/// When the bridge installs updates by itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct AutoInstall {
+    #[serde(default)]
+    pub on: bool,
+    /// The start of the window, in local time, e.g. "T14:00:00".
+    pub updatetime: Option<String>,
+}
+
+impl AutoInstall {
+    /// The start of the window as hours and minutes; updates are installed
+    /// within an hour after that.
+    pub fn update_time(&self) -> Option<(u8, u8)> {
+        let time = self.updatetime.as_deref()?.strip_prefix('T')?;
+        let mut parts = time.split(':').map(str::parse);
+        let hours = parts.next()?.ok().filter(|hours| *hours < 24)?;
+        let minutes = parts.next()?.ok().filter(|minutes| *minutes < 60)?;
+        Some((hours, minutes))
+    }
+}
+
+/// `swupdate2` in `GET /api/<username>/config`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct SoftwareUpdate {
+    /// The state of the bridge and all devices together.
+    #[serde(default)]
+    pub state: UpdateState,
+    /// The bridge itself.
+    #[serde(default)]
+    pub bridge: DeviceUpdate,
+    /// Whether the bridge is checking for updates right now.
+    #[serde(default)]
+    pub checkforupdate: bool,
+    pub lastchange: Option<String>,
+    #[serde(default)]
+    pub autoinstall: AutoInstall,
+}
+
+impl SoftwareUpdate {
+    /// Whether an update for the bridge or any device can be installed now.
+    pub fn is_ready(&self) -> bool {
+        self.state.is_ready() || self.bridge.state.is_ready()
+    }
+}
+
+/// Parse `swupdate2` from the response to
+/// [`Client::full_config_request`](crate::api::Client::full_config_request).
+pub fn parse_software_update(config: &Value) -> Result<SoftwareUpdate, serde_json::Error> {
+    SoftwareUpdate::deserialize(config.get("swupdate2").unwrap_or(&Value::Null))
+}
@@ -0,0 +1,262 @@
+//! Tracing the requests sent to the bridge, for debugging flaky bridges.
+//!
+//! Usernames and client keys are masked like in fixtures, see
+//! [`fixture::USERNAME`](crate::fixture::USERNAME), so that logs can be
+//! shared.
+use std::{
+    cell::{Cell, RefCell},
+    fmt, fs,
+    fs::File,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::api::{parse_response, Client, Error, Request};
+use crate::config::format_timestamp;
+use crate::fixture::{redact_path, Secrets};
+use crate::transport::{Response, Transport};
+
+/// How much to trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// Only requests that failed.
+    Errors,
+    /// Every request.
+    Requests,
+    /// Every request, with the request and response bodies.
+    Bodies,
+}
+
+impl Level {
+    /// Parse "errors", "requests" or "bodies".
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "errors" => Some(Self::Errors),
+            "requests" => Some(Self::Requests),
+            "bodies" => Some(Self::Bodies),
+            _ => None,
+        }
+    }
+}
+
+/// A traced request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+    pub time: SystemTime,
+    pub method: &'static str,
+    /// The bridge, since several may be traced to the same log.
+    pub host: String,
+    /// Without the scheme and host, and with the username masked.
+    pub path: String,
+    /// Not set if no response was received.
+    pub status_code: Option<u16>,
+    pub latency: Duration,
+    /// See [`error_kind`].
+    pub error: Option<String>,
+    /// Only at [`Level::Bodies`], and masked like the path.
+    pub request_body: Option<String>,
+    pub response_body: Option<String>,
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}Z {} {} {} ",
+            format_timestamp(self.time),
+            self.host,
+            self.method,
+            self.path
+        )?;
+        match self.status_code {
+            Some(status_code) => write!(f, "{status_code}")?,
+            None => write!(f, "-")?,
+        }
+        write!(f, " {}ms", self.latency.as_millis())?;
+        if let Some(error) = &self.error {
+            write!(f, " error={error}")?;
+        }
+        if let Some(body) = &self.request_body {
+            write!(f, "\n  > {body}")?;
+        }
+        if let Some(body) = &self.response_body {
+            write!(f, "\n  < {body}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A short, stable description of the kind of error, e.g. "transport",
+/// "http 503" or "hue 101".
+pub fn error_kind(err: &Error) -> String {
+    match err {
+        Error::Transport { .. } => "transport".to_string(),
+        Error::Cancelled { .. } => "cancelled".to_string(),
+        Error::Http { status_code, .. } => format!("http {status_code}"),
+        Error::Json { .. } => "json".to_string(),
+        Error::Hue { error, .. } => format!("hue {}", error.code()),
+        Error::InvalidResponse { .. } => "invalid response".to_string(),
+    }
+}
+
+/// Passes requests on to another transport, and reports a [`Trace`] of
+/// each of them.
+pub struct Tracer<T> {
+    inner: T,
+    client: Client,
+    level: Level,
+    on_trace: Rc<dyn Fn(&Trace)>,
+}
+
+impl<T: fmt::Debug> fmt::Debug for Tracer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tracer")
+            .field("inner", &self.inner)
+            .field("level", &self.level)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Tracer<T> {
+    /// `client` is the one whose requests are sent, for masking its
+    /// username and client key.
+    pub fn new(
+        inner: T,
+        client: Client,
+        level: Level,
+        on_trace: impl Fn(&Trace) + 'static,
+    ) -> Self {
+        Self {
+            inner,
+            client,
+            level,
+            on_trace: Rc::new(on_trace),
+        }
+    }
+}
+
+impl<T: Transport> Transport for Tracer<T> {
+    fn send(&self, request: Request, completion_handler: Box<dyn FnOnce(Result<Response, Error>)>) {
+        let client = self.client.clone();
+        let level = self.level;
+        let on_trace = self.on_trace.clone();
+        let method = request.method.as_str();
+        let url = request.url.clone();
+        let request_body = request.body.clone();
+        let start = Instant::now();
+        self.inner.send(
+            request,
+            Box::new(move |res| {
+                let latency = start.elapsed();
+                // Responses with an error object are failures too
+                let error = match &res {
+                    Ok(response) => parse_response(&url, response.status_code, &response.body)
+                        .err()
+                        .as_ref()
+                        .map(error_kind),
+                    Err(err) => Some(error_kind(err)),
+                };
+                if level >= Level::Requests || error.is_some() {
+                    let secrets = Secrets::of(&client);
+                    // Parse JSON to also mask the credentials returned by pairing
+                    let body = |body: &[u8]| match serde_json::from_slice(body) {
+                        Ok(json) => secrets.redact(json).to_string(),
+                        Err(_) => secrets.redact_str(&String::from_utf8_lossy(body)),
+                    };
+                    let with_bodies = |body: &&[u8]| level >= Level::Bodies && !body.is_empty();
+                    let trace = Trace {
+                        time: SystemTime::now() - latency,
+                        method,
+                        host: client.host().unwrap_or_default(),
+                        path: redact_path(&url),
+                        status_code: res.as_ref().ok().map(|response| response.status_code),
+                        latency,
+                        error,
+                        request_body: request_body.as_deref().filter(with_bodies).map(body),
+                        response_body: res
+                            .as_ref()
+                            .ok()
+                            .map(|response| &*response.body)
+                            .filter(with_bodies)
+                            .map(body),
+                    };
+                    on_trace(&trace);
+                }
+                completion_handler(res)
+            }),
+        );
+    }
+
+    fn run_after(&self, delay: Duration, f: Box<dyn FnOnce()>) {
+        self.inner.run_after(delay, f)
+    }
+}
+
+/// A log file that is moved aside when it gets too large.
+///
+/// When `path` would grow beyond the maximum length, it's renamed to
+/// `path.1`, `path.1` to `path.2` and so on, and the oldest is removed.
+#[derive(Debug)]
+pub struct RotatingLog {
+    path: PathBuf,
+    max_len: u64,
+    keep: usize,
+    file: RefCell<File>,
+    len: Cell<u64>,
+}
+
+impl RotatingLog {
+    /// Open the log at `path`, appending if it exists, and keeping `keep`
+    /// old logs besides it.
+    pub fn open(path: &Path, max_len: u64, keep: usize) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = File::options().create(true).append(true).open(path)?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_len,
+            keep,
+            file: RefCell::new(file),
+            len: Cell::new(len),
+        })
+    }
+
+    /// Append a line.
+    pub fn write_line(&self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.len.get() > 0 && self.len.get() + len > self.max_len {
+            self.rotate()?;
+        }
+        let mut file = self.file.borrow_mut();
+        file.write_all(line.as_bytes())?;
+        file.write_all(b"\n")?;
+        self.len.set(self.len.get() + len);
+        Ok(())
+    }
+
+    fn rotate(&self) -> io::Result<()> {
+        let old = |i: usize| {
+            let mut path = self.path.clone().into_os_string();
+            path.push(format!(".{i}"));
+            PathBuf::from(path)
+        };
+        if self.keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            for i in (1..self.keep).rev() {
+                match fs::rename(old(i), old(i + 1)) {
+                    Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                    _ => {}
+                }
+            }
+            fs::rename(&self.path, old(1))?;
+        }
+        *self.file.borrow_mut() = File::options().create(true).append(true).open(&self.path)?;
+        self.len.set(0);
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+//! The part of talking to the bridge that depends on the platform.
+//!
+//! [`Session`](crate::session::Session) does everything else: rate
+//! limiting, retrying and interpreting the responses.
+use std::fmt;
+use std::rc::Rc;
+use std::thread;
+use std::time::Duration;
+
+use crate::api::{Error, Request};
+use crate::http::HttpClient;
+
+/// A response to a [`Request`], with any status code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status_code: u16,
+    /// Header names are lowercase.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| &**value)
+    }
+}
+
+/// Sends requests, and waits.
+pub trait Transport: fmt::Debug {
+    /// Send `request`, and call `completion_handler` with the response, or
+    /// with [`Error::Transport`] or [`Error::Cancelled`] if there was none.
+    ///
+    /// The handler may be called before this returns.
+    fn send(&self, request: Request, completion_handler: Box<dyn FnOnce(Result<Response, Error>)>);
+
+    /// Call `f` after `delay`, on the same thread.
+    fn run_after(&self, delay: Duration, f: Box<dyn FnOnce()>);
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn send(&self, request: Request, completion_handler: Box<dyn FnOnce(Result<Response, Error>)>) {
+        (**self).send(request, completion_handler)
+    }
+
+    fn run_after(&self, delay: Duration, f: Box<dyn FnOnce()>) {
+        (**self).run_after(delay, f)
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Rc<T> {
+    fn send(&self, request: Request, completion_handler: Box<dyn FnOnce(Result<Response, Error>)>) {
+        (**self).send(request, completion_handler)
+    }
+
+    fn run_after(&self, delay: Duration, f: Box<dyn FnOnce()>) {
+        (**self).run_after(delay, f)
+    }
+}
+
+/// Blocks the calling thread, both while sending and while waiting.
+///
+/// Only for tests and command line tools: in an app, this would freeze the
+/// UI for the whole request, and for every retry delay and pairing
+/// interval.
+impl Transport for HttpClient {
+    fn send(&self, request: Request, completion_handler: Box<dyn FnOnce(Result<Response, Error>)>) {
+        completion_handler(HttpClient::send(self, &request));
+    }
+
+    fn run_after(&self, delay: Duration, f: Box<dyn FnOnce()>) {
+        thread::sleep(delay);
+        f();
+    }
+}
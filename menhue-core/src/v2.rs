@@ -0,0 +1,518 @@
+//! The Hue CLIP API v2.
+//!
+//! Resources are identified by UUIDs instead of the small numbers used in
+//! v1, and are served over HTTPS from `/clip/v2/resource/<type>`.
+//!
+//! See <https://developers.meethue.com/develop/hue-api-v2/>.
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::api::{self, parse_json, Error, Method, Request};
+use crate::error::HttpErrorBody;
+use crate::eventstream;
+use crate::light::GamutType;
+
+/// The header used for authenticating with the v2 API.
+///
+/// Its value is the same as the v1 username.
+pub const APPLICATION_KEY_HEADER: &str = "hue-application-key";
+
+/// The kinds of resources that we know about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceType {
+    Light,
+    GroupedLight,
+    Room,
+    Zone,
+    Scene,
+    Device,
+    Bridge,
+    BridgeHome,
+    ZigbeeConnectivity,
+    #[serde(other)]
+    Other,
+}
+
+impl ResourceType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Light => "light",
+            Self::GroupedLight => "grouped_light",
+            Self::Room => "room",
+            Self::Zone => "zone",
+            Self::Scene => "scene",
+            Self::Device => "device",
+            Self::Bridge => "bridge",
+            Self::BridgeHome => "bridge_home",
+            Self::ZigbeeConnectivity => "zigbee_connectivity",
+            Self::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for ResourceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A reference to another resource.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ResourceIdentifier {
+    pub rid: String,
+    pub rtype: ResourceType,
+}
+
+/// Common functionality of the resource types.
+pub trait Resource: DeserializeOwned {
+    const TYPE: ResourceType;
+
+    /// The UUID of the resource.
+    fn id(&self) -> &str;
+
+    /// The v1 address of the resource, e.g. `/lights/1`, if it has one.
+    fn id_v1(&self) -> Option<&str>;
+
+    fn identifier(&self) -> ResourceIdentifier {
+        ResourceIdentifier {
+            rid: self.id().to_string(),
+            rtype: Self::TYPE,
+        }
+    }
+}
+
+macro_rules! impl_resource {
+    ($($ty:ident => $rtype:ident,)*) => {$(
+        impl Resource for $ty {
+            const TYPE: ResourceType = ResourceType::$rtype;
+
+            fn id(&self) -> &str {
+                &self.id
+            }
+
+            fn id_v1(&self) -> Option<&str> {
+                self.id_v1.as_deref()
+            }
+        }
+    )*};
+}
+
+impl_resource! {
+    Light => Light,
+    GroupedLight => GroupedLight,
+    Room => Room,
+    Zone => Zone,
+    Scene => Scene,
+    Device => Device,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Metadata {
+    pub name: String,
+    pub archetype: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct On {
+    pub on: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Dimming {
+    /// Brightness in percent.
+    pub brightness: f64,
+    /// The lowest brightness the light supports, in percent.
+    pub min_dim_level: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct MirekSchema {
+    pub mirek_minimum: u16,
+    pub mirek_maximum: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct ColorTemperature {
+    /// The color temperature, or `None` if the light is in color mode.
+    pub mirek: Option<u16>,
+    #[serde(default)]
+    pub mirek_valid: bool,
+    pub mirek_schema: Option<MirekSchema>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Xy {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Gamut {
+    pub red: Xy,
+    pub green: Xy,
+    pub blue: Xy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Color {
+    pub xy: Xy,
+    pub gamut: Option<Gamut>,
+    pub gamut_type: Option<GamutType>,
+}
+
+/// A `light` resource.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Light {
+    pub id: String,
+    pub id_v1: Option<String>,
+    /// The device that the light is part of.
+    pub owner: ResourceIdentifier,
+    pub metadata: Metadata,
+    pub on: On,
+    pub dimming: Option<Dimming>,
+    pub color_temperature: Option<ColorTemperature>,
+    pub color: Option<Color>,
+    /// "normal" or "streaming".
+    pub mode: Option<String>,
+}
+
+/// A `grouped_light` resource, the service used for controlling all lights
+/// in a room or zone at once.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct GroupedLight {
+    pub id: String,
+    pub id_v1: Option<String>,
+    /// The room, zone or bridge home that the lights belong to.
+    pub owner: ResourceIdentifier,
+    pub on: Option<On>,
+    pub dimming: Option<Dimming>,
+}
+
+/// A `room` resource.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Room {
+    pub id: String,
+    pub id_v1: Option<String>,
+    pub metadata: Metadata,
+    /// The devices in the room.
+    #[serde(default)]
+    pub children: Vec<ResourceIdentifier>,
+    /// Usually a single `grouped_light`.
+    #[serde(default)]
+    pub services: Vec<ResourceIdentifier>,
+}
+
+impl Room {
+    /// The `grouped_light` service for controlling the whole room.
+    pub fn grouped_light(&self) -> Option<&str> {
+        grouped_light(&self.services)
+    }
+}
+
+/// A `zone` resource.
+///
+/// Unlike rooms, zones contain lights directly, and a light may be in
+/// several zones.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Zone {
+    pub id: String,
+    pub id_v1: Option<String>,
+    pub metadata: Metadata,
+    /// The lights in the zone.
+    #[serde(default)]
+    pub children: Vec<ResourceIdentifier>,
+    #[serde(default)]
+    pub services: Vec<ResourceIdentifier>,
+}
+
+impl Zone {
+    /// The `grouped_light` service for controlling the whole zone.
+    pub fn grouped_light(&self) -> Option<&str> {
+        grouped_light(&self.services)
+    }
+}
+
+fn grouped_light(services: &[ResourceIdentifier]) -> Option<&str> {
+    services
+        .iter()
+        .find(|service| service.rtype == ResourceType::GroupedLight)
+        .map(|service| &*service.rid)
+}
+
+/// A `scene` resource.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Scene {
+    pub id: String,
+    pub id_v1: Option<String>,
+    pub metadata: Metadata,
+    /// The room or zone that the scene belongs to.
+    pub group: ResourceIdentifier,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ProductData {
+    pub model_id: Option<String>,
+    pub manufacturer_name: Option<String>,
+    pub product_name: Option<String>,
+    pub software_version: Option<String>,
+}
+
+/// A `device` resource, the physical thing that provides e.g. a `light`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Device {
+    pub id: String,
+    pub id_v1: Option<String>,
+    pub product_data: Option<ProductData>,
+    pub metadata: Metadata,
+    #[serde(default)]
+    pub services: Vec<ResourceIdentifier>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DimmingUpdate {
+    /// Brightness in percent.
+    pub brightness: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ColorTemperatureUpdate {
+    pub mirek: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ColorUpdate {
+    pub xy: Xy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Dynamics {
+    /// Transition time in milliseconds.
+    pub duration: u32,
+}
+
+/// A change to a `light` or `grouped_light`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LightUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on: Option<On>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimming: Option<DimmingUpdate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color_temperature: Option<ColorTemperatureUpdate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<ColorUpdate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dynamics: Option<Dynamics>,
+}
+
+/// The envelope that all v2 responses are wrapped in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response<T> {
+    pub data: Vec<T>,
+    /// The bridge may partially succeed, e.g. when changing several
+    /// attributes of a light.
+    pub errors: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct RawResponse<T> {
+    #[serde(default = "Vec::new")]
+    data: Vec<T>,
+    #[serde(default)]
+    errors: Vec<RawError>,
+}
+
+#[derive(Deserialize)]
+struct RawError {
+    description: String,
+}
+
+/// Interpret a response from the v2 API.
+pub fn parse_response<T: DeserializeOwned>(
+    url: &str,
+    status_code: u16,
+    body: &[u8],
+) -> Result<Response<T>, Error> {
+    let json = parse_json(url, status_code, body)?;
+    let response: RawResponse<T> = serde_json::from_value(json).map_err(|error| Error::Json {
+        url: url.to_string(),
+        error,
+    })?;
+    let errors: Vec<String> = response
+        .errors
+        .into_iter()
+        .map(|error| error.description)
+        .collect();
+
+    // Nothing succeeded, so report it as a failure
+    if response.data.is_empty() && !errors.is_empty() {
+        return Err(Error::Http {
+            url: url.to_string(),
+            status_code,
+            body: HttpErrorBody::V2(errors),
+        });
+    }
+
+    Ok(Response {
+        data: response.data,
+        errors,
+    })
+}
+
+/// Talks to the v2 API of the same bridge as a v1 [`api::Client`].
+///
+/// Shares the host and username (known as the application key in v2) with
+/// the v1 client.
+#[derive(Debug, Clone, Default)]
+pub struct Client {
+    v1: api::Client,
+}
+
+impl Client {
+    pub fn new(v1: api::Client) -> Self {
+        Self { v1 }
+    }
+
+    pub fn v1(&self) -> &api::Client {
+        &self.v1
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        self.v1.url_with_scheme("https", path)
+    }
+
+    pub fn request(&self, method: Method, path: &str, json: Option<&Value>) -> Request {
+        let mut headers = vec![("Content-Type".to_string(), "application/json".to_string())];
+        if let Some(username) = self.v1.username() {
+            headers.push((APPLICATION_KEY_HEADER.to_string(), username));
+        }
+        Request {
+            method,
+            url: self.url(path),
+            headers,
+            body: json.map(|json| serde_json::to_vec(json).expect("json writing")),
+        }
+    }
+
+    /// Fetch all resources of type `T`.
+    ///
+    /// Pass the response to [`parse_response::<T>`].
+    pub fn get_all<T: Resource>(&self) -> Request {
+        self.request(Method::Get, &format!("/clip/v2/resource/{}", T::TYPE), None)
+    }
+
+    /// Fetch a single resource of type `T`.
+    pub fn get<T: Resource>(&self, id: &str) -> Request {
+        self.request(
+            Method::Get,
+            &format!("/clip/v2/resource/{}/{id}", T::TYPE),
+            None,
+        )
+    }
+
+    /// Change a `light` or `grouped_light`.
+    ///
+    /// Pass the response to [`parse_response::<ResourceIdentifier>`].
+    pub fn update_light(&self, identifier: &ResourceIdentifier, update: &LightUpdate) -> Request {
+        let json = serde_json::to_value(update).expect("serializing light update");
+        self.request(
+            Method::Put,
+            &format!("/clip/v2/resource/{}/{}", identifier.rtype, identifier.rid),
+            Some(&json),
+        )
+    }
+
+    /// Subscribe to the event stream.
+    ///
+    /// The response never finishes; feed the body to an
+    /// [`eventstream::Parser`] as it arrives.
+    pub fn event_stream(&self) -> Request {
+        let mut request = self.request(Method::Get, eventstream::PATH, None);
+        request
+            .headers
+            .push(("Accept".to_string(), "text/event-stream".to_string()));
+        request
+    }
+
+    /// Recall a scene.
+    pub fn recall_scene(&self, id: &str) -> Request {
+        self.request(
+            Method::Put,
+            &format!("/clip/v2/resource/scene/{id}"),
+            Some(&serde_json::json!({ "recall": { "action": "active" } })),
+        )
+    }
+}
+
+/// Translates between v1 addresses like `/lights/1` and v2 UUIDs.
+///
+/// Note that a v1 group corresponds to both a `room` or `zone` and a
+/// `grouped_light`, so lookups from v1 need the resource type.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdMap {
+    from_v1: HashMap<(String, ResourceType), String>,
+    to_v1: HashMap<String, String>,
+}
+
+impl IdMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the ids of the given resources.
+    pub fn extend<'a, T: Resource + 'a>(&mut self, resources: impl IntoIterator<Item = &'a T>) {
+        for resource in resources {
+            if let Some(id_v1) = resource.id_v1() {
+                self.insert(id_v1, resource.identifier());
+            }
+        }
+    }
+
+    pub fn insert(&mut self, id_v1: &str, identifier: ResourceIdentifier) {
+        self.to_v1.insert(identifier.rid.clone(), id_v1.to_string());
+        self.from_v1
+            .insert((id_v1.to_string(), identifier.rtype), identifier.rid);
+    }
+
+    /// The v2 id of the resource with the given v1 address and type.
+    pub fn v2_id(&self, id_v1: &str, rtype: ResourceType) -> Option<&str> {
+        self.from_v1
+            .get(&(id_v1.to_string(), rtype))
+            .map(|rid| &**rid)
+    }
+
+    /// The v1 address of the resource with the given v2 id.
+    pub fn v1_id(&self, rid: &str) -> Option<&str> {
+        self.to_v1.get(rid).map(|id_v1| &**id_v1)
+    }
+
+    /// The v2 id of the v1 light with the given number.
+    pub fn light(&self, light_id: &str) -> Option<&str> {
+        self.v2_id(&format!("/lights/{light_id}"), ResourceType::Light)
+    }
+
+    /// The v2 `grouped_light` id of the v1 group with the given number.
+    pub fn grouped_light(&self, group_id: &str) -> Option<&str> {
+        self.v2_id(&format!("/groups/{group_id}"), ResourceType::GroupedLight)
+    }
+}
+
+/// A v1 resource that isn't in an [`IdMap`], e.g. because it was added
+/// after the map was fetched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownResource {
+    pub id_v1: String,
+}
+
+impl fmt::Display for UnknownResource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no v2 resource for {}", self.id_v1)
+    }
+}
+
+impl std::error::Error for UnknownResource {}
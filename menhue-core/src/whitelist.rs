@@ -0,0 +1,162 @@
+//! The users that may access the bridge, which the bridge calls its
+//! whitelist.
+//!
+//! Every pairing creates a new user, and the bridge never forgets them, so
+//! they pile up. Bridges from around 2019 refuse deleting users through
+//! the local API; they can only be deleted through the remote API, which
+//! needs an access token for the owner's Hue account.
+use std::{
+    fmt,
+    time::{Duration, SystemTime},
+};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::api::{Error, Method, Request};
+use crate::config::parse_timestamp;
+use crate::error::HueErrorKind;
+use crate::resource::parse_resources;
+
+/// The remote API, for bridges that are linked to a Hue account.
+pub const REMOTE_API_URL: &str = "https://api.meethue.com/route";
+
+/// A user, as listed in `GET /api/<username>/config`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct User {
+    /// The key in the `whitelist` dictionary.
+    #[serde(skip)]
+    pub username: String,
+    /// The devicetype given when pairing, e.g. "menhue#macbook".
+    pub name: String,
+    /// In the format of [`format_timestamp`](crate::config::format_timestamp).
+    #[serde(rename = "create date")]
+    pub create_date: Option<String>,
+    /// Not updated on every request, only about once a day.
+    #[serde(rename = "last use date")]
+    pub last_use_date: Option<String>,
+}
+
+impl User {
+    pub fn created(&self) -> Option<SystemTime> {
+        self.create_date.as_deref().and_then(parse_timestamp)
+    }
+
+    pub fn last_used(&self) -> Option<SystemTime> {
+        self.last_use_date.as_deref().and_then(parse_timestamp)
+    }
+
+    /// Whether the user hasn't been used for `max_age` before `now`.
+    ///
+    /// Users that were never used count from when they were created, and
+    /// users without either date are never stale.
+    pub fn is_stale(&self, now: SystemTime, max_age: Duration) -> bool {
+        self.last_used()
+            .or_else(|| self.created())
+            .and_then(|time| now.duration_since(time).ok())
+            .is_some_and(|age| age > max_age)
+    }
+}
+
+/// Parse the users from the response to
+/// [`Client::full_config_request`](crate::api::Client::full_config_request).
+///
+/// They are sorted by username.
+pub fn parse_whitelist(config: &Value) -> Result<Vec<User>, serde_json::Error> {
+    let whitelist = config.get("whitelist").unwrap_or(&Value::Null);
+    parse_resources(whitelist, |user: &mut User, username| {
+        user.username = username
+    })
+}
+
+/// Users that must not be deleted, besides the one a
+/// [`Session`](crate::session::Session) is using.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Protected {
+    /// E.g. the users of the other bridges in the settings.
+    pub usernames: Vec<String>,
+    /// E.g. [`pairing::devicetype`](crate::pairing::devicetype) of this
+    /// computer, whose users may still be in use by another install.
+    pub devicetypes: Vec<String>,
+}
+
+impl Protected {
+    pub fn contains(&self, user: &User) -> bool {
+        self.usernames.contains(&user.username) || self.devicetypes.contains(&user.name)
+    }
+}
+
+/// Why a user couldn't be deleted.
+#[derive(Debug)]
+pub enum DeleteError {
+    /// The user is the one this install uses, or one of the
+    /// [`Protected`] users.
+    Protected {
+        username: String,
+    },
+    /// The bridge only allows deleting users through the remote API, see
+    /// [`remote_delete_request`].
+    NeedsRemote(Error),
+    Api(Error),
+}
+
+impl fmt::Display for DeleteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Protected { .. } => write!(f, "refusing to delete a user that may be in use"),
+            Self::NeedsRemote(err) => write!(
+                f,
+                "the bridge only allows deleting users through the remote API: {err}"
+            ),
+            Self::Api(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for DeleteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Protected { .. } => None,
+            Self::NeedsRemote(err) | Self::Api(err) => Some(err),
+        }
+    }
+}
+
+impl From<Error> for DeleteError {
+    /// Bridges that refuse deleting users locally respond as if the user
+    /// we're authenticated with wasn't allowed to.
+    fn from(err: Error) -> Self {
+        let refused = err.hue_error().is_some_and(|error| {
+            error.address.contains("/whitelist/")
+                && matches!(
+                    error.kind,
+                    HueErrorKind::UnauthorizedUser | HueErrorKind::MethodNotAvailable
+                )
+        });
+        if refused {
+            Self::NeedsRemote(err)
+        } else {
+            Self::Api(err)
+        }
+    }
+}
+
+/// The request that deletes `username` through the remote API.
+///
+/// Only the request is built here; sending it is left to the caller. It
+/// goes to the Hue cloud instead of the bridge, so it can't be sent over
+/// a bridge's [`Transport`](crate::transport::Transport), which only
+/// trusts the bridge's certificate. Getting `access_token`, an OAuth token for the Hue account that
+/// the bridge is linked to, and `remote_username`, a user created through
+/// the remote API, is also up to the caller.
+pub fn remote_delete_request(access_token: &str, remote_username: &str, username: &str) -> Request {
+    Request {
+        method: Method::Delete,
+        url: format!("{REMOTE_API_URL}/api/{remote_username}/config/whitelist/{username}"),
+        headers: vec![(
+            "Authorization".to_string(),
+            format!("Bearer {access_token}"),
+        )],
+        body: None,
+    }
+}
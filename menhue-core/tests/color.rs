@@ -0,0 +1,136 @@
+//! The RGB conversions against the reference values Philips publishes for
+//! Hue lights.
+use menhue_core::color::{
+    bri_to_brightness, brightness_to_bri, format_hex, parse_hex, rgb_to_xy, xy_to_rgb, Gamut,
+};
+
+const RED: [u8; 3] = [255, 0, 0];
+const GREEN: [u8; 3] = [0, 255, 0];
+const BLUE: [u8; 3] = [0, 0, 255];
+const WHITE: [u8; 3] = [255, 255, 255];
+
+/// Reference values are given to 4 decimals.
+fn assert_xy(rgb: [u8; 3], gamut: &Gamut, expected: [f64; 2]) {
+    let (xy, _) = rgb_to_xy(rgb, gamut);
+    assert!(
+        (xy[0] - expected[0]).abs() < 5e-4 && (xy[1] - expected[1]).abs() < 5e-4,
+        "{rgb:?} in {gamut:?}: expected {expected:?}, got {xy:?}"
+    );
+}
+
+#[test]
+fn primaries_in_gamut_a() {
+    assert_xy(RED, &Gamut::A, [0.7006, 0.2993]);
+    assert_xy(GREEN, &Gamut::A, [0.2151, 0.7106]);
+    assert_xy(BLUE, &Gamut::A, [0.138, 0.08]);
+}
+
+#[test]
+fn primaries_in_gamut_b() {
+    assert_xy(RED, &Gamut::B, [0.675, 0.322]);
+    assert_xy(GREEN, &Gamut::B, [0.409, 0.518]);
+    assert_xy(BLUE, &Gamut::B, [0.167, 0.04]);
+}
+
+#[test]
+fn primaries_in_gamut_c() {
+    assert_xy(RED, &Gamut::C, [0.6915, 0.3083]);
+    assert_xy(GREEN, &Gamut::C, [0.17, 0.7]);
+    assert_xy(BLUE, &Gamut::C, [0.1532, 0.0475]);
+}
+
+#[test]
+fn primaries_unclamped() {
+    assert_xy(RED, &Gamut::FULL, [0.7006, 0.2993]);
+    assert_xy(GREEN, &Gamut::FULL, [0.1724, 0.7468]);
+    assert_xy(BLUE, &Gamut::FULL, [0.1355, 0.0399]);
+}
+
+#[test]
+fn white() {
+    for gamut in [Gamut::A, Gamut::B, Gamut::C, Gamut::FULL] {
+        assert_xy(WHITE, &gamut, [0.3227, 0.329]);
+        let (_, brightness) = rgb_to_xy(WHITE, &gamut);
+        assert!((brightness - 1.0).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn black_is_dark_white() {
+    let (xy, brightness) = rgb_to_xy([0, 0, 0], &Gamut::C);
+    assert_eq!(xy, [0.3227, 0.329]);
+    assert_eq!(brightness, 0.0);
+    assert_eq!(xy_to_rgb(xy, brightness, &Gamut::C), [0, 0, 0]);
+}
+
+#[test]
+fn brightness_is_luminance() {
+    assert!((rgb_to_xy(RED, &Gamut::C).1 - 0.2839).abs() < 5e-4);
+    assert!((rgb_to_xy(GREEN, &Gamut::C).1 - 0.6684).abs() < 5e-4);
+    assert!((rgb_to_xy(BLUE, &Gamut::C).1 - 0.0477).abs() < 5e-4);
+}
+
+#[test]
+fn closest_point_in_gamut() {
+    // Inside stays where it is
+    assert_eq!(Gamut::C.closest([0.4, 0.4]), [0.4, 0.4]);
+    // Beyond a corner moves to the corner
+    assert_eq!(Gamut::B.closest([0.8, 0.3]), Gamut::B.red);
+    // Beyond an edge moves onto the edge
+    let xy = Gamut::A.closest([0.1, 0.4]);
+    assert!(Gamut::A.contains(xy));
+    assert!(!Gamut::A.contains([0.1, 0.4]));
+}
+
+#[test]
+fn round_trip() {
+    for rgb in [RED, GREEN, BLUE, WHITE, [255, 165, 0], [18, 52, 86]] {
+        let (xy, brightness) = rgb_to_xy(rgb, &Gamut::FULL);
+        let back = xy_to_rgb(xy, brightness, &Gamut::FULL);
+        for (a, b) in rgb.iter().zip(back) {
+            assert!(a.abs_diff(b) <= 1, "{rgb:?} came back as {back:?}");
+        }
+    }
+}
+
+#[test]
+fn gamut_of_light() {
+    let gamut = |control: serde_json::Value| {
+        let json = serde_json::json!({
+            "name": "Light",
+            "state": {},
+            "capabilities": { "control": control },
+        });
+        Gamut::of_light(&serde_json::from_value(json).unwrap())
+    };
+    assert_eq!(
+        gamut(serde_json::json!({ "colorgamuttype": "B" })),
+        Gamut::B
+    );
+    let reported = gamut(serde_json::json!({
+        "colorgamuttype": "C",
+        "colorgamut": [[0.68, 0.31], [0.17, 0.7], [0.15, 0.05]],
+    }));
+    assert_eq!(reported.red, [0.68, 0.31]);
+    assert_eq!(
+        gamut(serde_json::json!({ "colorgamuttype": "Other" })),
+        Gamut::FULL
+    );
+    assert_eq!(gamut(serde_json::json!({})), Gamut::FULL);
+}
+
+#[test]
+fn bri() {
+    assert_eq!(brightness_to_bri(1.0), 254);
+    assert_eq!(brightness_to_bri(0.0), 1);
+    assert_eq!(brightness_to_bri(bri_to_brightness(100)), 100);
+}
+
+#[test]
+fn hex() {
+    assert_eq!(parse_hex("#ff8800"), Some([255, 136, 0]));
+    assert_eq!(parse_hex("FF8800"), Some([255, 136, 0]));
+    assert_eq!(parse_hex("#ff880"), None);
+    assert_eq!(parse_hex("+f8800"), None);
+    assert_eq!(format_hex([255, 136, 0]), "#ff8800");
+}
@@ -0,0 +1,103 @@
+//! The bridge's public configuration, and the timestamps it reports.
+use std::time::{Duration, UNIX_EPOCH};
+
+use menhue_core::config::{format_timestamp, parse_config, parse_timestamp, BridgeConfig};
+use serde_json::json;
+
+fn config(apiversion: &str) -> BridgeConfig {
+    parse_config(&json!({
+        "name": "Philips hue",
+        "bridgeid": "001788FFFE6B1B41",
+        "modelid": "BSB002",
+        "apiversion": apiversion,
+        "swversion": "1956020000",
+        "datastoreversion": "163"
+    }))
+    .unwrap()
+}
+
+#[test]
+fn api_versions() {
+    assert_eq!(config("1.56.0").api_version(), Some((1, 56, 0)));
+    assert_eq!(config("1.48").api_version(), Some((1, 48, 0)));
+    assert_eq!(config("2").api_version(), Some((2, 0, 0)));
+    assert_eq!(config("").api_version(), None);
+    assert_eq!(config("1.x.0").api_version(), None);
+}
+
+#[test]
+fn supports_v2() {
+    assert!(!config("1.47.0").supports_v2());
+    assert!(!config("1.47.99").supports_v2());
+    assert!(config("1.48.0").supports_v2());
+    assert!(config("1.48").supports_v2());
+    assert!(config("1.56.0").supports_v2());
+    assert!(config("2.0.0").supports_v2());
+    // Unknown, so don't try
+    assert!(!config("garbage").supports_v2());
+    assert!(!config("").supports_v2());
+}
+
+#[test]
+fn old_firmware() {
+    let config = parse_config(&json!({
+        "name": "Philips hue",
+        "bridgeid": "001788FFFE6B1B41",
+        "modelid": "BSB001",
+        "apiversion": "1.16.0",
+        "swversion": "01036659"
+    }))
+    .unwrap();
+    assert_eq!(config.datastoreversion, None);
+    assert!(!config.supports_v2());
+
+    assert!(parse_config(&json!({ "name": "Philips hue" })).is_err());
+}
+
+#[test]
+fn format_timestamps() {
+    assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00");
+    assert_eq!(
+        format_timestamp(UNIX_EPOCH + Duration::from_secs(1_706_702_400)),
+        "2024-01-31T12:00:00"
+    );
+    // A leap day
+    assert_eq!(
+        format_timestamp(UNIX_EPOCH + Duration::from_secs(1_709_251_199)),
+        "2024-02-29T23:59:59"
+    );
+}
+
+#[test]
+fn timestamp_round_trip() {
+    for secs in [
+        0,
+        59,
+        86_399,
+        951_782_400, // 2000-02-29
+        1_706_702_400,
+        1_709_251_199,
+        4_102_444_800, // 2100-01-01
+    ] {
+        let time = UNIX_EPOCH + Duration::from_secs(secs);
+        let formatted = format_timestamp(time);
+        assert_eq!(parse_timestamp(&formatted), Some(time), "{formatted}");
+    }
+    for s in ["2024-01-31T12:00:00", "1999-12-31T23:59:59"] {
+        assert_eq!(format_timestamp(parse_timestamp(s).unwrap()), s);
+    }
+}
+
+#[test]
+fn invalid_timestamps() {
+    assert_eq!(parse_timestamp("none"), None);
+    assert_eq!(parse_timestamp(""), None);
+    assert_eq!(parse_timestamp("2024-01-31"), None);
+    assert_eq!(parse_timestamp("2024-01-31T12:00"), None);
+    assert_eq!(parse_timestamp("2024-13-01T12:00:00"), None);
+    assert_eq!(parse_timestamp("2024-01-32T12:00:00"), None);
+    assert_eq!(parse_timestamp("2024-01-31T24:00:00"), None);
+    assert_eq!(parse_timestamp("2024-01-31T12:60:00"), None);
+    // Before 1970
+    assert_eq!(parse_timestamp("1969-12-31T23:59:59"), None);
+}
@@ -0,0 +1,266 @@
+//! Discovering bridges with mDNS and SSDP, against responders on localhost.
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use menhue_core::discovery::{self, mdns, ssdp, Candidate, Options};
+use menhue_core::emulator::Bridge;
+
+const TIMEOUT: Duration = Duration::from_millis(300);
+
+/// Answer the first query with `responses`, one datagram each.
+fn responder(responses: Vec<Vec<u8>>) -> SocketAddr {
+    let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let addr = socket.local_addr().unwrap();
+    thread::spawn(move || {
+        let mut buf = [0; 9000];
+        let (_, from) = socket.recv_from(&mut buf).unwrap();
+        for response in responses {
+            socket.send_to(&response, from).unwrap();
+        }
+    });
+    addr
+}
+
+fn candidate(address: [u8; 4], port: u16, bridge_id: Option<&str>) -> Candidate {
+    Candidate {
+        address: Ipv4Addr::from(address).into(),
+        port,
+        bridge_id: bridge_id.map(str::to_string),
+    }
+}
+
+fn ssdp_response(server: &str, extra_headers: &str) -> Vec<u8> {
+    format!(
+        "HTTP/1.1 200 OK\r\n\
+         CACHE-CONTROL: max-age=100\r\n\
+         EXT:\r\n\
+         SERVER: {server}\r\n\
+         ST: upnp:rootdevice\r\n\
+         {extra_headers}\
+         \r\n"
+    )
+    .into_bytes()
+}
+
+#[test]
+fn ssdp_responses() {
+    let bridge = ssdp_response(
+        "Hue/1.0 UPnP/1.0 IpBridge/1.65.0",
+        "LOCATION: http://192.168.1.2:80/description.xml\r\n\
+         hue-bridgeid: 001788fffe123456\r\n",
+    );
+    let target = responder(vec![
+        bridge.clone(),
+        // Bridges answer once for each search target
+        bridge,
+        // Not a bridge
+        ssdp_response(
+            "Linux/3.14 UPnP/1.0 Sonos/70.3",
+            "LOCATION: http://192.168.1.9:1400/xml/device_description.xml\r\n",
+        ),
+        // Without a location the address it came from is used
+        ssdp_response("Hue/1.0 UPnP/1.0 IpBridge/1.65.0", ""),
+        b"NOTIFY * HTTP/1.1\r\n\r\n".to_vec(),
+        b"garbage".to_vec(),
+    ]);
+
+    assert_eq!(
+        ssdp::discover_at(target, TIMEOUT).unwrap(),
+        [
+            candidate([192, 168, 1, 2], 443, Some("001788FFFE123456")),
+            candidate([127, 0, 0, 1], 443, None),
+        ]
+    );
+}
+
+fn dns_name(name: &str) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    for label in name.split('.') {
+        encoded.push(label.len() as u8);
+        encoded.extend_from_slice(label.as_bytes());
+    }
+    encoded.push(0);
+    encoded
+}
+
+fn dns_record(name: &[u8], kind: u16, rdata: &[u8]) -> Vec<u8> {
+    let mut record = name.to_vec();
+    record.extend_from_slice(&kind.to_be_bytes());
+    // Class IN with the "cache flush" bit, and a TTL
+    record.extend_from_slice(&0x8001u16.to_be_bytes());
+    record.extend_from_slice(&120u32.to_be_bytes());
+    record.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+    record.extend_from_slice(rdata);
+    record
+}
+
+fn dns_message(flags: u16, records: &[Vec<u8>]) -> Vec<u8> {
+    let mut msg = vec![0, 0];
+    msg.extend_from_slice(&flags.to_be_bytes());
+    // No questions, all records are answers
+    msg.extend_from_slice(&[0, 0]);
+    msg.extend_from_slice(&(records.len() as u16).to_be_bytes());
+    msg.extend_from_slice(&[0, 0, 0, 0]);
+    msg.extend(records.concat());
+    msg
+}
+
+/// The records a bridge announces itself with.
+fn bridge_records(instance: &str, bridge_id: &str, port: u16, address: [u8; 4]) -> Vec<Vec<u8>> {
+    let instance = dns_name(&format!("{instance}._hue._tcp.local"));
+    let host = dns_name(&format!("{bridge_id}.local"));
+    let mut srv = vec![0, 0, 0, 0];
+    srv.extend_from_slice(&port.to_be_bytes());
+    srv.extend_from_slice(&host);
+    let txt_entry = format!("bridgeid={bridge_id}");
+    let mut txt = vec![txt_entry.len() as u8];
+    txt.extend_from_slice(txt_entry.as_bytes());
+    txt.extend_from_slice(b"\x0bmodelid=BSB002");
+    vec![
+        dns_record(&dns_name(mdns::SERVICE), 12, &instance),
+        dns_record(&instance, 33, &srv),
+        dns_record(&instance, 16, &txt),
+        dns_record(&host, 1, &address),
+    ]
+}
+
+#[test]
+fn mdns_responses() {
+    let first = bridge_records(
+        "Hue Bridge - 123456",
+        "001788fffe123456",
+        443,
+        [192, 168, 1, 2],
+    );
+    let second = bridge_records(
+        "Hue Bridge - 654321",
+        "001788fffe654321",
+        8443,
+        [192, 168, 1, 3],
+    );
+    let target = responder(vec![
+        // Our own query, as seen on the multicast group
+        dns_message(0, &first),
+        dns_message(0x8400, &first),
+        // Answered again by another responder
+        dns_message(0x8400, &first),
+        // Split across two responses
+        dns_message(0x8400, &second[..2]),
+        dns_message(0x8400, &second[2..]),
+        // Truncated
+        dns_message(0x8400, &first)[..40].to_vec(),
+    ]);
+
+    assert_eq!(
+        mdns::discover_at(target, TIMEOUT).unwrap(),
+        [
+            candidate([192, 168, 1, 2], 443, Some("001788FFFE123456")),
+            candidate([192, 168, 1, 3], 8443, Some("001788FFFE654321")),
+        ]
+    );
+}
+
+#[test]
+fn mdns_without_address_records() {
+    let instance = dns_name("Hue Bridge - 123456._hue._tcp.local");
+    let target = responder(vec![dns_message(
+        0x8400,
+        &[dns_record(&dns_name(mdns::SERVICE), 12, &instance)],
+    )]);
+
+    assert_eq!(
+        mdns::discover_at(target, TIMEOUT).unwrap(),
+        [candidate([127, 0, 0, 1], 443, None)]
+    );
+}
+
+#[test]
+fn mdns_instances_of_the_same_bridge() {
+    // The same bridge under two names, once without its id
+    let mut records = bridge_records(
+        "Hue Bridge - 123456",
+        "001788fffe123456",
+        443,
+        [127, 0, 0, 1],
+    );
+    records.push(dns_record(
+        &dns_name(mdns::SERVICE),
+        12,
+        &dns_name("Hue Bridge - old._hue._tcp.local"),
+    ));
+    let target = responder(vec![dns_message(0x8400, &records)]);
+
+    assert_eq!(
+        mdns::discover_at(target, TIMEOUT).unwrap(),
+        [candidate([127, 0, 0, 1], 443, Some("001788FFFE123456"))]
+    );
+}
+
+#[test]
+fn nobody_answers() {
+    let target = responder(Vec::new());
+    let start = Instant::now();
+    assert_eq!(ssdp::discover_at(target, TIMEOUT).unwrap(), []);
+    assert!(start.elapsed() >= TIMEOUT);
+}
+
+#[test]
+fn candidate_hosts() {
+    assert_eq!(candidate([192, 168, 1, 2], 443, None).host(), "192.168.1.2");
+    assert_eq!(
+        candidate([127, 0, 0, 1], 8080, None).host(),
+        "127.0.0.1:8080"
+    );
+}
+
+#[test]
+fn validate_against_the_emulator() {
+    let bridge = Bridge::start().unwrap();
+    let port = bridge.addr().port();
+
+    let validated = discovery::validate(&candidate([127, 0, 0, 1], port, None), TIMEOUT).unwrap();
+    assert_eq!(
+        validated,
+        candidate([127, 0, 0, 1], port, Some("001788FFFE000001"))
+    );
+
+    let err = discovery::validate(
+        &candidate([127, 0, 0, 1], port, Some("001788FFFE123456")),
+        TIMEOUT,
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("expected bridge 001788FFFE123456, found 001788FFFE000001"),
+        "{err}"
+    );
+}
+
+#[test]
+fn discover_the_emulator() {
+    let bridge = Bridge::start().unwrap();
+    let records = bridge_records(
+        "Hue Bridge - 000001",
+        "001788fffe000001",
+        bridge.addr().port(),
+        [127, 0, 0, 1],
+    );
+    let options = Options {
+        timeout: TIMEOUT,
+        mdns: Some(responder(vec![dns_message(0x8400, &records)])),
+        ssdp: None,
+        nupnp: None,
+    };
+
+    let discovered = discovery::discover(&options);
+    assert_eq!(discovered.errors, Vec::<String>::new());
+    assert_eq!(
+        discovered.bridges,
+        [candidate(
+            [127, 0, 0, 1],
+            bridge.addr().port(),
+            Some("001788FFFE000001")
+        )]
+    );
+}
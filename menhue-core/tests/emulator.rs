@@ -0,0 +1,248 @@
+//! Driving a [`Session`] over HTTP against the emulated bridge.
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use menhue_core::api::{Client, Error, Method};
+use menhue_core::emulator::{Bridge, Failure};
+use menhue_core::error::HueErrorKind;
+use menhue_core::http::HttpClient;
+use menhue_core::light::{InvalidUpdate, Light, StateUpdate};
+use menhue_core::pairing::PairingError;
+use menhue_core::session::Session;
+use serde_json::json;
+
+const USERNAME: &str = "emulator-test-user";
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A session with a user that's already paired.
+fn logged_in(bridge: &Bridge) -> Session {
+    bridge.with_state(|state| state.add_user(USERNAME, "menhue#test"));
+    session(bridge, Some(USERNAME))
+}
+
+fn session(bridge: &Bridge, username: Option<&str>) -> Session {
+    let client = Client::new(Some(bridge.host()), username.map(str::to_string));
+    Session::new(HttpClient::new(TIMEOUT), client)
+}
+
+/// Call `f` with a completion handler, and return what it was called with.
+///
+/// [`HttpClient`] blocks until it's done, so the handler has been called
+/// by the time `f` returns.
+fn wait<T: 'static>(f: impl FnOnce(Box<dyn FnOnce(T)>)) -> T {
+    let result = Rc::new(RefCell::new(None));
+    f(Box::new({
+        let result = result.clone();
+        move |res| *result.borrow_mut() = Some(res)
+    }));
+    let result = result.borrow_mut().take();
+    result.expect("completion handler was called")
+}
+
+fn light(session: &Session, id: &str) -> Light {
+    let lights = wait(|done| session.lights(done)).unwrap();
+    lights.into_iter().find(|light| light.id == id).unwrap()
+}
+
+/// Count the retries of `session`.
+fn count_retries(session: &Session) -> Rc<RefCell<u32>> {
+    let retries = Rc::new(RefCell::new(0));
+    session.set_on_retry({
+        let retries = retries.clone();
+        move |_, _| *retries.borrow_mut() += 1
+    });
+    retries
+}
+
+#[test]
+fn pairing_waits_for_the_link_button() {
+    let bridge = Rc::new(Bridge::start().unwrap());
+    let session = session(&bridge, None);
+
+    let waiting = Rc::new(RefCell::new(0));
+    let credentials = wait(|done| {
+        let bridge = bridge.clone();
+        let waiting = waiting.clone();
+        session.pair(
+            "menhue#test".to_string(),
+            move |_| {
+                *waiting.borrow_mut() += 1;
+                bridge.press_link_button();
+            },
+            done,
+        )
+    })
+    .unwrap();
+
+    assert_eq!(*waiting.borrow(), 1);
+    assert_eq!(
+        session.client().username(),
+        Some(credentials.username.clone())
+    );
+    assert!(credentials.clientkey.is_some());
+    let user = bridge.with_state(|state| state.users[&credentials.username].clone());
+    assert_eq!(user.devicetype, "menhue#test");
+    assert_eq!(user.clientkey, credentials.clientkey);
+}
+
+#[test]
+fn set_light_state() {
+    let bridge = Bridge::start().unwrap();
+    let session = logged_in(&bridge);
+    let ceiling = light(&session, "2");
+
+    let update = StateUpdate {
+        on: Some(true),
+        bri: Some(100),
+        hue: Some(0),
+        sat: Some(0),
+        ..Default::default()
+    };
+    let result = wait(|done| session.update_light(&ceiling, update, done).unwrap()).unwrap();
+    assert!(result.is_ok(), "{result:?}");
+    assert_eq!(result.success("/lights/2/state/bri"), Some(&json!(100)));
+
+    let state = light(&session, "2").state;
+    assert_eq!(state.on, Some(true));
+    assert_eq!(state.bri, Some(100));
+    assert_eq!(state.sat, Some(0));
+}
+
+#[test]
+fn unsupported_updates_are_not_sent() {
+    let bridge = Bridge::start().unwrap();
+    let session = logged_in(&bridge);
+    let desk = light(&session, "1");
+
+    let update = StateUpdate {
+        ct: Some(300),
+        ..Default::default()
+    };
+    assert_eq!(
+        session.update_light(&desk, update, |_| panic!("sent an invalid update")),
+        Err(InvalidUpdate::Unsupported { attribute: "ct" })
+    );
+}
+
+#[test]
+fn attributes_fail_separately() {
+    let bridge = Bridge::start().unwrap();
+    let session = logged_in(&bridge);
+    let desk = light(&session, "1");
+
+    // The light is off
+    let update = StateUpdate {
+        bri: Some(100),
+        ..Default::default()
+    };
+    let result = wait(|done| session.update_light(&desk, update, done).unwrap()).unwrap();
+    let error = result.error("/lights/1/state/bri").unwrap();
+    assert_eq!(error.kind, HueErrorKind::DeviceOff);
+}
+
+#[test]
+fn recall_scene() {
+    let bridge = Bridge::start().unwrap();
+    let session = logged_in(&bridge);
+    let scenes = wait(|done| session.scenes(done)).unwrap();
+    let scene = scenes.iter().find(|scene| scene.name == "Bright").unwrap();
+
+    let client = session.client();
+    let group = scene.group.as_deref().unwrap();
+    let request = client.request(
+        Method::Put,
+        &client.authenticated_path(&format!("/groups/{group}/action")),
+        Some(&json!({ "scene": scene.id })),
+    );
+    let result = wait(|done| session.send_update(request, done)).unwrap();
+    assert!(result.is_ok(), "{result:?}");
+
+    for id in ["1", "2"] {
+        let state = light(&session, id).state;
+        assert_eq!(state.on, Some(true));
+        assert_eq!(state.bri, Some(254));
+    }
+    assert_eq!(light(&session, "2").state.ct, Some(233));
+}
+
+#[test]
+fn injected_hue_error() {
+    let bridge = Bridge::start().unwrap();
+    let session = logged_in(&bridge);
+    let retries = count_retries(&session);
+
+    bridge.fail_next(Failure::Hue(3));
+    let err = wait(|done| session.lights(done)).unwrap_err();
+    assert_eq!(
+        err.hue_error().map(|error| error.kind),
+        Some(HueErrorKind::ResourceNotAvailable)
+    );
+    assert_eq!(*retries.borrow(), 0);
+}
+
+#[test]
+fn injected_internal_error_is_retried() {
+    let bridge = Bridge::start().unwrap();
+    let session = logged_in(&bridge);
+    let retries = count_retries(&session);
+    let ceiling = light(&session, "2");
+
+    bridge.fail_next(Failure::Hue(901));
+    let update = StateUpdate {
+        on: Some(true),
+        ..Default::default()
+    };
+    let result = wait(|done| session.update_light(&ceiling, update, done).unwrap()).unwrap();
+    assert!(result.is_ok(), "{result:?}");
+    assert_eq!(*retries.borrow(), 1);
+}
+
+#[test]
+fn delay() {
+    let bridge = Bridge::start().unwrap();
+    let session = logged_in(&bridge);
+
+    bridge.set_delay(Duration::from_millis(200));
+    let start = Instant::now();
+    wait(|done| session.lights(done)).unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
+
+#[test]
+fn delay_beyond_the_timeout() {
+    let bridge = Bridge::start().unwrap();
+    bridge.with_state(|state| state.add_user(USERNAME, "menhue#test"));
+    let client = Client::new(Some(bridge.host()), Some(USERNAME.to_string()));
+    let session = Session::new(HttpClient::new(Duration::from_millis(100)), client);
+    let retries = count_retries(&session);
+
+    bridge.set_delay(Duration::from_millis(500));
+    let err = wait(|done| session.lights(done)).unwrap_err();
+    assert!(matches!(err, Error::Transport { .. }), "{err}");
+    // Three attempts in all
+    assert_eq!(*retries.borrow(), 2);
+}
+
+#[test]
+fn disconnect() {
+    let bridge = Bridge::start().unwrap();
+    let session = logged_in(&bridge);
+    let retries = count_retries(&session);
+
+    // Reads are sent again
+    bridge.fail_next(Failure::Disconnect);
+    let lights = wait(|done| session.lights(done)).unwrap();
+    assert_eq!(lights.len(), 3);
+    assert_eq!(*retries.borrow(), 1);
+
+    // Pairing isn't, as it creates a user each time
+    bridge.press_link_button();
+    bridge.fail_next(Failure::Disconnect);
+    let err = wait(|done| session.pair("menhue#test".to_string(), |_| {}, done)).unwrap_err();
+    assert!(
+        matches!(err, PairingError::Api(Error::Transport { .. })),
+        "{err}"
+    );
+    assert_eq!(*retries.borrow(), 1);
+}
@@ -0,0 +1,231 @@
+//! Interpreting error responses from the bridge, and from whatever else
+//! may answer in its place.
+use menhue_core::api::parse_response;
+use menhue_core::error::{HttpErrorBody, HueError, HueErrorKind};
+use serde_json::json;
+
+const USERNAME: &str = "0123456789abcdefghijklmnopqrstuvwxyzABCD";
+
+#[test]
+fn classify_bodies() {
+    assert_eq!(HttpErrorBody::parse(b""), HttpErrorBody::Empty);
+    assert_eq!(HttpErrorBody::parse(b" \r\n"), HttpErrorBody::Empty);
+
+    assert_eq!(
+        HttpErrorBody::parse(
+            br#"[{"error":{"type":3,"address":"/lights/9","description":"resource, /lights/9, not available"}}]"#
+        ),
+        HttpErrorBody::Hue(vec![HueError::new(
+            3,
+            "/lights/9",
+            "resource, /lights/9, not available"
+        )])
+    );
+    // Without the array
+    assert_eq!(
+        HttpErrorBody::parse(
+            br#"{"error":{"type":901,"address":"/","description":"Internal error, 503"}}"#
+        ),
+        HttpErrorBody::Hue(vec![HueError::new(901, "/", "Internal error, 503")])
+    );
+
+    assert_eq!(
+        HttpErrorBody::parse(br#"{"errors":[{"description":"unauthorized user"}],"data":[]}"#),
+        HttpErrorBody::V2(vec!["unauthorized user".to_string()])
+    );
+    assert_eq!(
+        HttpErrorBody::parse(br#"{"errors":[{"code":5}]}"#),
+        HttpErrorBody::V2(vec![r#"{"code":5}"#.to_string()])
+    );
+
+    assert_eq!(
+        HttpErrorBody::parse(br#"{"message":"no"}"#),
+        HttpErrorBody::Json(json!({ "message": "no" }))
+    );
+    assert_eq!(HttpErrorBody::parse(b"[]"), HttpErrorBody::Json(json!([])));
+
+    let html = "<!DOCTYPE html>\n<html><head><TITLE> 503 Service Unavailable </TITLE></head><body></body></html>";
+    assert_eq!(
+        HttpErrorBody::parse(html.as_bytes()),
+        HttpErrorBody::Html {
+            title: Some("503 Service Unavailable".to_string()),
+            html: html.to_string(),
+        }
+    );
+    assert_eq!(
+        HttpErrorBody::parse(b"<html><body>Not Found</body></html>"),
+        HttpErrorBody::Html {
+            title: None,
+            html: "<html><body>Not Found</body></html>".to_string(),
+        }
+    );
+
+    assert_eq!(
+        HttpErrorBody::parse(b"Service Unavailable\n"),
+        HttpErrorBody::Text("Service Unavailable\n".to_string())
+    );
+}
+
+#[test]
+fn display_bodies() {
+    assert_eq!(HttpErrorBody::Empty.to_string(), "empty body");
+    assert_eq!(
+        HttpErrorBody::parse(b"<html><title>Proxy Error</title></html>").to_string(),
+        r#"HTML page "Proxy Error""#
+    );
+    assert_eq!(
+        HttpErrorBody::V2(vec!["a".to_string(), "b".to_string()]).to_string(),
+        "a, b"
+    );
+    // Long bodies are cut off
+    let text = HttpErrorBody::Text("é".repeat(300)).to_string();
+    assert_eq!(text, format!("{}...", "é".repeat(200)));
+}
+
+#[test]
+fn error_kinds() {
+    let error = HueError::from_json(&json!({
+        "type": 101, "address": "", "description": "link button not pressed"
+    }))
+    .unwrap();
+    assert_eq!(error.kind, HueErrorKind::LinkButtonNotPressed);
+    assert_eq!(error.code(), 101);
+    assert_eq!(error.to_string(), "link button not pressed (type 101)");
+
+    let error = HueError::from_json(&json!({ "type": 1234 })).unwrap();
+    assert_eq!(error.kind, HueErrorKind::Other(1234));
+    assert_eq!(error.code(), 1234);
+    assert_eq!(error.description, "no error description");
+
+    assert_eq!(HueError::from_json(&json!("error")), None);
+}
+
+#[test]
+fn mask_usernames_in_addresses() {
+    let addresses = [
+        format!("/config/whitelist/{USERNAME}"),
+        format!("/api/{USERNAME}/config/whitelist/{USERNAME}"),
+        format!("/config/whitelist/{USERNAME}/name"),
+    ];
+    for address in addresses {
+        let error = HueError::new(1, address, "unauthorized user");
+        let message = error.to_string();
+        assert!(!message.contains(USERNAME), "{message}");
+        assert!(message.contains("/whitelist/<username>"), "{message}");
+    }
+
+    // Also in the body of a failed request, and in the error as a whole
+    let url = format!("http://192.168.1.2/api/{USERNAME}/config/whitelist/{USERNAME}");
+    let body = format!(
+        r#"[{{"error":{{"type":1,"address":"/config/whitelist/{USERNAME}","description":"unauthorized user"}}}}]"#
+    );
+    for status_code in [200, 403] {
+        let err = parse_response(&url, status_code, body.as_bytes()).unwrap_err();
+        let message = err.to_string();
+        assert!(!message.contains(USERNAME), "{message}");
+    }
+
+    // Descriptions repeat the address
+    let error = HueError::new(
+        3,
+        format!("/config/whitelist/{USERNAME}"),
+        format!("resource, /config/whitelist/{USERNAME}, not available"),
+    );
+    assert_eq!(
+        error.to_string(),
+        "resource, /config/whitelist/<username>, not available \
+         (type 3 at /config/whitelist/<username>)"
+    );
+
+    let error = HueError::new(
+        7,
+        "/lights/1/state/bri",
+        "invalid value, 300, for parameter, bri",
+    );
+    assert_eq!(
+        error.to_string(),
+        "invalid value, 300, for parameter, bri (type 7 at /lights/1/state/bri)"
+    );
+}
+
+const URL: &str = "http://192.168.1.2/api/user/lights";
+const PROXY_PAGE: &[u8] = b"<html><head><title>503 Service Unavailable</title></head>\n\
+    <body><h1>503 Service Unavailable</h1>No server is available to handle this request.</body></html>";
+
+#[test]
+fn unauthorized() {
+    // v1 responds with 200 and an error object
+    let err = parse_response(
+        URL,
+        200,
+        br#"[{"error":{"type":1,"address":"/","description":"unauthorized user"}}]"#,
+    )
+    .unwrap_err();
+    assert!(err.is_unauthorized());
+    // v2 with 403
+    let err = parse_response(
+        URL,
+        403,
+        br#"{"errors":[{"description":"unauthorized user"}],"data":[]}"#,
+    )
+    .unwrap_err();
+    assert!(err.is_unauthorized());
+
+    // Something else in the way
+    let err = parse_response(URL, 403, b"<html><title>Forbidden</title></html>").unwrap_err();
+    assert!(!err.is_unauthorized());
+    let err = parse_response(
+        URL,
+        200,
+        br#"[{"error":{"type":901,"address":"/lights","description":"Internal error, 503"}}]"#,
+    )
+    .unwrap_err();
+    assert!(!err.is_unauthorized());
+}
+
+#[test]
+fn overloaded() {
+    let err = parse_response(URL, 503, b"").unwrap_err();
+    assert!(err.is_overloaded());
+    let err = parse_response(URL, 503, PROXY_PAGE).unwrap_err();
+    assert!(err.is_overloaded());
+
+    // Only the status code counts, not the bridge's internal error
+    let err = parse_response(
+        URL,
+        200,
+        br#"[{"error":{"type":901,"address":"/lights","description":"Internal error, 503"}}]"#,
+    )
+    .unwrap_err();
+    assert!(!err.is_overloaded());
+    let err = parse_response(URL, 500, b"").unwrap_err();
+    assert!(!err.is_overloaded());
+}
+
+#[test]
+fn from_proxy() {
+    let err = parse_response(URL, 503, PROXY_PAGE).unwrap_err();
+    assert!(err.is_from_proxy());
+    for status_code in [407, 502, 504] {
+        let err = parse_response(URL, status_code, b"").unwrap_err();
+        assert!(err.is_from_proxy(), "{status_code}");
+    }
+
+    // The bridge itself
+    let err = parse_response(URL, 503, b"").unwrap_err();
+    assert!(!err.is_from_proxy());
+    let err = parse_response(
+        URL,
+        200,
+        br#"[{"error":{"type":1,"address":"/","description":"unauthorized user"}}]"#,
+    )
+    .unwrap_err();
+    assert!(!err.is_from_proxy());
+    let err = parse_response(
+        URL,
+        404,
+        br#"{"errors":[{"description":"Not Found"}],"data":[]}"#,
+    )
+    .unwrap_err();
+    assert!(!err.is_from_proxy());
+}
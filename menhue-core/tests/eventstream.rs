@@ -0,0 +1,256 @@
+//! Splitting the event stream into events, and applying them to the cached
+//! lights.
+use std::time::Duration;
+
+use menhue_core::cache::LightCache;
+use menhue_core::eventstream::{parse_events, Backoff, Event, EventType, Message, Parser};
+use menhue_core::light::{parse_lights, ColorMode};
+use menhue_core::v2::ResourceType;
+use serde_json::json;
+
+/// Two lights, as returned from `GET /api/<username>/lights`.
+fn lights_json() -> serde_json::Value {
+    json!({
+        "1": {
+            "name": "Desk",
+            "state": {
+                "on": false, "bri": 100, "xy": [0.4573, 0.41], "ct": 366,
+                "colormode": "xy", "reachable": true
+            }
+        },
+        "2": { "name": "Hall", "state": { "on": true, "bri": 254, "reachable": true } }
+    })
+}
+
+fn connected_cache() -> LightCache {
+    let cache = LightCache::new();
+    cache.set_connected(true);
+    cache.set_lights(parse_lights(&lights_json()).unwrap());
+    cache
+}
+
+/// The events in a message from the bridge.
+fn events(data: serde_json::Value) -> Vec<Event> {
+    parse_events(&Message {
+        data: data.to_string(),
+        ..Default::default()
+    })
+    .unwrap()
+}
+
+fn event(kind: &str, data: serde_json::Value) -> Event {
+    let mut events = events(json!([{
+        "creationtime": "2024-01-31T12:00:00Z",
+        "id": "0c9a6c3f-5c85-4a6e-8d27-8d2a0a0f3a4d",
+        "type": kind,
+        "data": data,
+    }]));
+    events.remove(0)
+}
+
+#[test]
+fn parse_messages() {
+    let mut parser = Parser::new();
+    let messages = parser.feed(b": hi\n\nid: 1:0\ndata: [1,\ndata: 2]\n\n");
+    assert_eq!(
+        messages,
+        vec![Message {
+            id: Some("1:0".to_string()),
+            event: None,
+            data: "[1,\n2]".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn parse_messages_split_across_chunks() {
+    let mut parser = Parser::new();
+    let stream = b"id: 1\r\nevent: update\r\ndata: []\r\n\r\nid: 2\r\ndata: {}\r\n\r\n";
+    let mut messages = Vec::new();
+    // Every possible split, including inside the CRLF
+    for chunk in stream.chunks(3) {
+        messages.extend(parser.feed(chunk));
+    }
+    assert_eq!(
+        messages,
+        vec![
+            Message {
+                id: Some("1".to_string()),
+                event: Some("update".to_string()),
+                data: "[]".to_string(),
+            },
+            Message {
+                id: Some("2".to_string()),
+                event: None,
+                data: "{}".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn ignore_comments_and_messages_without_data() {
+    let mut parser = Parser::new();
+    assert_eq!(parser.feed(b": hi\n\n"), Vec::new());
+    // Only the id, which isn't dispatched
+    assert_eq!(parser.feed(b"id: 1\nretry: 1000\n\n"), Vec::new());
+    // Not dispatched until the empty line
+    assert_eq!(parser.feed(b"data:no space"), Vec::new());
+    assert_eq!(parser.feed(b"\n"), Vec::new());
+    assert_eq!(parser.feed(b"\n")[0].data, "no space");
+}
+
+#[test]
+fn parse_bridge_events() {
+    let events = events(json!([{
+        "creationtime": "2024-01-31T12:00:00Z",
+        "data": [{
+            "id": "3ab27084-d02f-44b9-bd56-70ea41163cb6",
+            "id_v1": "/lights/1",
+            "on": { "on": true },
+            "owner": { "rid": "8cb0d4b1-5fd0-4d6e-9b39-f0a4b9b5d1d2", "rtype": "device" },
+            "type": "light"
+        }],
+        "id": "0c9a6c3f-5c85-4a6e-8d27-8d2a0a0f3a4d",
+        "type": "update"
+    }]));
+    assert_eq!(events[0].kind, EventType::Update);
+    assert_eq!(events[0].data[0].rtype, ResourceType::Light);
+    assert!(events[0].data[0].on.unwrap().on);
+}
+
+#[test]
+fn backoff() {
+    let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(10));
+    let delays: Vec<u64> = (0..6).map(|_| backoff.next_delay().as_secs()).collect();
+    assert_eq!(delays, [1, 2, 4, 8, 10, 10]);
+
+    backoff.reset();
+    assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+
+    // Doesn't overflow after many failures
+    let mut backoff = Backoff::default();
+    for _ in 0..100 {
+        backoff.next_delay();
+    }
+    assert_eq!(backoff.next_delay(), Duration::from_secs(60));
+}
+
+#[test]
+fn cache_only_while_connected() {
+    let cache = LightCache::new();
+    cache.set_lights(parse_lights(&lights_json()).unwrap());
+    assert_eq!(cache.lights(), None);
+
+    let cache = connected_cache();
+    assert_eq!(cache.lights().unwrap().len(), 2);
+    // Events may be missed while reconnecting
+    cache.set_connected(false);
+    cache.set_connected(true);
+    assert_eq!(cache.lights(), None);
+}
+
+#[test]
+fn apply_updates() {
+    let cache = connected_cache();
+    let changed = cache.apply(&event(
+        "update",
+        json!([{
+            "id": "3ab27084-d02f-44b9-bd56-70ea41163cb6",
+            "id_v1": "/lights/1",
+            "type": "light",
+            "on": { "on": true },
+            "dimming": { "brightness": 50.0 },
+            "color_temperature": { "mirek": 250, "mirek_valid": true }
+        }]),
+    ));
+    assert!(changed);
+    let lights = cache.lights().unwrap();
+    let state = &lights[0].state;
+    assert_eq!(state.on, Some(true));
+    assert_eq!(state.bri, Some(127));
+    assert_eq!(state.ct, Some(250));
+    assert_eq!(state.colormode, Some(ColorMode::Ct));
+    // Untouched
+    assert_eq!(state.xy, Some([0.4573, 0.41]));
+    assert_eq!(lights[1], parse_lights(&lights_json()).unwrap()[1]);
+
+    let changed = cache.apply(&event(
+        "update",
+        json!([{
+            "id": "3ab27084-d02f-44b9-bd56-70ea41163cb6",
+            "id_v1": "/lights/1",
+            "type": "light",
+            "color": { "xy": { "x": 0.3, "y": 0.3 } },
+            "dimming": { "brightness": 0.0 }
+        }]),
+    ));
+    assert!(changed);
+    let state = &cache.lights().unwrap()[0].state;
+    assert_eq!(state.xy, Some([0.3, 0.3]));
+    assert_eq!(state.colormode, Some(ColorMode::Xy));
+    // The lowest v1 brightness
+    assert_eq!(state.bri, Some(1));
+}
+
+#[test]
+fn apply_connectivity() {
+    let cache = connected_cache();
+    let changed = cache.apply(&event(
+        "update",
+        json!([{
+            "id": "5e1f7a0c-3d2b-4c8e-9f6a-1b2c3d4e5f60",
+            "id_v1": "/lights/2",
+            "type": "zigbee_connectivity",
+            "status": "connectivity_issue"
+        }]),
+    ));
+    assert!(changed);
+    assert!(!cache.lights().unwrap()[1].is_reachable());
+}
+
+#[test]
+fn apply_unchanged() {
+    let cache = connected_cache();
+    let update = |id_v1: &str| {
+        event(
+            "update",
+            json!([{
+                "id": "3ab27084-d02f-44b9-bd56-70ea41163cb6",
+                "id_v1": id_v1,
+                "type": "light",
+                "on": { "on": false }
+            }]),
+        )
+    };
+    // Already off
+    assert!(!cache.apply(&update("/lights/1")));
+    // Not a cached light
+    assert!(!cache.apply(&update("/lights/9")));
+    // Groups are ignored
+    assert!(!cache.apply(&event(
+        "update",
+        json!([{ "id": "f2b6bc6e", "id_v1": "/groups/1", "type": "grouped_light", "on": { "on": true } }]),
+    )));
+}
+
+#[test]
+fn apply_add_and_delete() {
+    let cache = connected_cache();
+    let changed = cache.apply(&event(
+        "delete",
+        json!([{ "id": "3ab27084-d02f-44b9-bd56-70ea41163cb6", "id_v1": "/lights/1", "type": "light" }]),
+    ));
+    assert!(changed);
+    let lights = cache.lights().unwrap();
+    assert_eq!(lights.len(), 1);
+    assert_eq!(lights[0].id, "2");
+
+    // Added lights have to be fetched
+    let changed = cache.apply(&event(
+        "add",
+        json!([{ "id": "7d0a1e2b-9c8f-4e3d-a6b5-0f1e2d3c4b5a", "id_v1": "/lights/3", "type": "light" }]),
+    ));
+    assert!(changed);
+    assert_eq!(cache.lights(), None);
+}
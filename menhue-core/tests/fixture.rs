@@ -0,0 +1,106 @@
+//! Recording the traffic with the emulated bridge, and replaying it.
+use std::cell::RefCell;
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
+
+use menhue_core::api::Client;
+use menhue_core::emulator::Bridge;
+use menhue_core::fixture::{Recorder, Replay};
+use menhue_core::http::HttpClient;
+use menhue_core::light::{Light, StateUpdate};
+use menhue_core::session::Session;
+
+fn fixture_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("menhue-{name}-{}.jsonl", std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+/// Returns what the completion handler passed to `f` is called with, once
+/// it is.
+fn result<T: 'static>(f: impl FnOnce(Box<dyn FnOnce(T)>)) -> Rc<RefCell<Option<T>>> {
+    let result = Rc::new(RefCell::new(None));
+    f(Box::new({
+        let result = result.clone();
+        move |res| *result.borrow_mut() = Some(res)
+    }));
+    result
+}
+
+fn on(light: &str) -> StateUpdate {
+    StateUpdate {
+        on: Some(true),
+        bri: (light != "3").then_some(200),
+        ..Default::default()
+    }
+}
+
+/// Pair, fetch the lights and turn them on.
+fn exercise(session: &Session) -> Vec<Light> {
+    let credentials = result(|done| session.pair("menhue#test".to_string(), |_| {}, done));
+    credentials.take().unwrap().unwrap();
+
+    let lights = result(|done| session.lights(done)).take().unwrap().unwrap();
+    for light in &lights {
+        // `HttpClient` waits out the rate limit before returning
+        let updated = result(|done| session.update_light(light, on(&light.id), done).unwrap());
+        assert!(updated.take().unwrap().unwrap().is_ok());
+    }
+    lights
+}
+
+#[test]
+fn round_trip() {
+    let bridge = Bridge::start().unwrap();
+    bridge.press_link_button();
+    let path = fixture_path("round-trip");
+
+    let client = Client::new(Some(bridge.host()), None);
+    let recorder = Recorder::new(
+        HttpClient::new(Duration::from_secs(2)),
+        client.clone(),
+        &path,
+    )
+    .unwrap();
+    let session = Session::new(recorder, client.clone());
+    let recorded = exercise(&session);
+
+    let username = client.username().unwrap();
+    let clientkey = client.clientkey().unwrap();
+    let fixture = fs::read_to_string(&path).unwrap();
+    assert!(!fixture.contains(&username), "{fixture}");
+    assert!(!fixture.contains(&clientkey), "{fixture}");
+    assert!(fixture.contains("/api/<username>/lights"), "{fixture}");
+    assert!(
+        fixture.contains(r#""clientkey":"<clientkey>""#),
+        "{fixture}"
+    );
+
+    let replay = Rc::new(Replay::load(&path).unwrap());
+    let client = Client::new(Some("bridge.invalid".to_string()), None);
+    let session = Session::new(replay.clone(), client.clone());
+    let replayed = {
+        let credentials = result(|done| session.pair("menhue#test".to_string(), |_| {}, done));
+        credentials.take().unwrap().unwrap();
+        result(|done| session.lights(done)).take().unwrap().unwrap()
+    };
+    assert_eq!(replayed, recorded);
+    assert_eq!(client.username().as_deref(), Some("<username>"));
+
+    // The rate limit holds back all but the first update
+    let updates: Vec<_> = replayed
+        .iter()
+        .map(|light| result(|done| session.update_light(light, on(&light.id), done).unwrap()))
+        .collect();
+    assert!(updates[0].borrow().is_some());
+    assert!(updates[1].borrow().is_none());
+    // Waiting is left to the caller
+    replay.run_delayed();
+    for updated in updates {
+        assert!(updated.take().unwrap().unwrap().is_ok());
+    }
+
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,165 @@
+//! Parsing lights as reported by the bridge, and checking updates against
+//! what they support.
+use menhue_core::color::Gamut;
+use menhue_core::light::{
+    parse_lights, ColorMode, CtRange, GamutType, InvalidUpdate, Light, StateUpdate,
+};
+use serde_json::json;
+
+/// A Hue color lamp, as returned from `GET /api/<username>/lights`.
+fn color_lamp() -> serde_json::Value {
+    json!({
+        "state": {
+            "on": true, "bri": 144, "hue": 13088, "sat": 212, "effect": "none",
+            "xy": [0.5128, 0.4147], "ct": 467, "alert": "none", "colormode": "xy",
+            "mode": "homeautomation", "reachable": true
+        },
+        "swupdate": { "state": "noupdates", "lastinstall": "2024-01-01T03:00:00" },
+        "type": "Extended color light",
+        "name": "Hue color lamp 1",
+        "modelid": "LCT015",
+        "manufacturername": "Signify Netherlands B.V.",
+        "productname": "Hue color lamp",
+        "capabilities": {
+            "certified": true,
+            "control": {
+                "mindimlevel": 1000,
+                "maxlumen": 806,
+                "colorgamuttype": "C",
+                "colorgamut": [[0.6915, 0.3083], [0.17, 0.7], [0.1532, 0.0475]],
+                "ct": { "min": 153, "max": 500 }
+            },
+            "streaming": { "renderer": true, "proxy": true }
+        },
+        "config": { "archetype": "sultanbulb", "function": "mixed", "direction": "omnidirectional" },
+        "uniqueid": "00:17:88:01:03:a2:4b:8c-0b",
+        "swversion": "1.88.1"
+    })
+}
+
+fn light(json: serde_json::Value) -> Light {
+    let lights = parse_lights(&json!({ "1": json })).unwrap();
+    lights.into_iter().next().expect("the light parsed")
+}
+
+#[test]
+fn parse_a_color_lamp() {
+    let light = light(color_lamp());
+    assert_eq!(light.id, "1");
+    assert_eq!(light.name, "Hue color lamp 1");
+    assert_eq!(light.state.colormode, Some(ColorMode::Xy));
+    assert_eq!(
+        light.capabilities.control.colorgamuttype,
+        Some(GamutType::C)
+    );
+    assert_eq!(
+        light.capabilities.control.ct,
+        Some(CtRange { min: 153, max: 500 })
+    );
+    assert!(light.has_brightness() && light.has_color() && light.has_color_temperature());
+}
+
+#[test]
+fn missing_optional_fields() {
+    // A plug from another manufacturer
+    let light = light(json!({ "name": "Plug", "state": { "on": true } }));
+    assert_eq!(light.kind, None);
+    assert_eq!(light.modelid, None);
+    assert_eq!(light.swupdate, None);
+    assert_eq!(light.capabilities, Default::default());
+    assert_eq!(light.config, Default::default());
+    assert!(light.is_reachable());
+    assert!(light.state.is_on());
+    assert!(!light.has_brightness());
+    assert!(!light.has_color());
+    assert!(!light.has_color_temperature());
+
+    // A white light that only reports its ct range
+    let light = self::light(json!({
+        "name": "White ambiance",
+        "state": { "on": false, "bri": 1, "reachable": false },
+        "capabilities": { "control": { "ct": { "min": 153, "max": 454 } } }
+    }));
+    assert!(!light.is_reachable());
+    assert!(light.has_brightness());
+    assert!(light.has_color_temperature());
+    assert!(!light.has_color());
+}
+
+#[test]
+fn unknown_gamut() {
+    let mut json = color_lamp();
+    json["capabilities"]["control"]["colorgamuttype"] = json!("other");
+    json["capabilities"]["control"]
+        .as_object_mut()
+        .unwrap()
+        .remove("colorgamut");
+    let light = light(json);
+    assert_eq!(
+        light.capabilities.control.colorgamuttype,
+        Some(GamutType::Other)
+    );
+    assert!(light.has_color());
+    assert_eq!(Gamut::of_light(&light), Gamut::FULL);
+
+    let mut json = color_lamp();
+    json["capabilities"]["control"]["colorgamuttype"] = json!("D");
+    assert_eq!(
+        self::light(json).capabilities.control.colorgamuttype,
+        Some(GamutType::Other)
+    );
+}
+
+#[test]
+fn ct_range() {
+    let range = CtRange { min: 153, max: 454 };
+    assert!(range.contains(153) && range.contains(454));
+    assert!(!range.contains(152) && !range.contains(455));
+    assert_eq!(range.clamp(100), 153);
+    assert_eq!(range.clamp(500), 454);
+    assert_eq!(range.clamp(300), 300);
+    // A broken range, which `u16::clamp` would panic on
+    assert_eq!(CtRange { min: 454, max: 153 }.clamp(500), 454);
+
+    let ct = |ct| StateUpdate {
+        ct: Some(ct),
+        ..Default::default()
+    };
+    let mut json = color_lamp();
+    json["capabilities"]["control"]["ct"] = json!({ "min": 153, "max": 454 });
+    let light = light(json);
+    assert_eq!(ct(454).validate(&light), Ok(()));
+    assert_eq!(
+        ct(500).validate(&light),
+        Err(InvalidUpdate::OutOfRange { attribute: "ct" })
+    );
+
+    // Without a reported range, the one the bridge accepts
+    let light = self::light(json!({ "name": "Old", "state": { "on": true, "ct": 366 } }));
+    assert_eq!(ct(500).validate(&light), Ok(()));
+    assert_eq!(
+        ct(501).validate(&light),
+        Err(InvalidUpdate::OutOfRange { attribute: "ct" })
+    );
+    assert_eq!(
+        ct(152).validate(&light),
+        Err(InvalidUpdate::OutOfRange { attribute: "ct" })
+    );
+}
+
+#[test]
+fn skip_lights_that_do_not_parse() {
+    let lights = parse_lights(&json!({
+        "10": color_lamp(),
+        "2": { "name": "No state" },
+        "3": { "name": "Bad brightness", "state": { "bri": 1000 } },
+        "4": "not a light",
+        "1": { "name": "Plug", "state": { "on": true } },
+    }))
+    .unwrap();
+    let ids: Vec<&str> = lights.iter().map(|light| &*light.id).collect();
+    assert_eq!(ids, ["1", "10"]);
+
+    assert!(parse_lights(&json!([])).is_err());
+    assert!(parse_lights(&json!({})).unwrap().is_empty());
+}
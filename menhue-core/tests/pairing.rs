@@ -0,0 +1,181 @@
+//! Deciding when to ask the bridge again while the user goes to press its
+//! link button, and keeping the credentials it then responds with.
+use std::time::{Duration, Instant};
+
+use menhue_core::api::{parse_response, Client, Error};
+use menhue_core::error::HueError;
+use menhue_core::pairing::{devicetype, Credentials, Pairing, PairingError, Step};
+
+const URL: &str = "http://192.168.1.2/api";
+
+fn link_button_not_pressed() -> Result<Credentials, Error> {
+    Err(Error::Hue {
+        url: URL.to_string(),
+        error: HueError::new(101, "", "link button not pressed"),
+    })
+}
+
+fn credentials() -> Credentials {
+    Credentials {
+        username: "0123456789abcdefghijklmnopqrstuvwxyzABCD".to_string(),
+        clientkey: Some("0123456789ABCDEF0123456789ABCDEF".to_string()),
+    }
+}
+
+#[test]
+fn retry_until_the_button_is_pressed() {
+    let start = Instant::now();
+    let pairing = Pairing::new(start);
+
+    let step = pairing.handle(start, link_button_not_pressed());
+    assert!(
+        matches!(
+            step,
+            Step::Retry { after, remaining }
+                if after == Pairing::INTERVAL && remaining == Pairing::TIMEOUT
+        ),
+        "{step:?}"
+    );
+
+    let now = start + Duration::from_secs(30);
+    let step = pairing.handle(now, link_button_not_pressed());
+    assert!(
+        matches!(
+            step,
+            Step::Retry { after, remaining }
+                if after == Pairing::INTERVAL && remaining == Duration::from_secs(30)
+        ),
+        "{step:?}"
+    );
+
+    let step = pairing.handle(now, Ok(credentials()));
+    assert!(
+        matches!(step, Step::Done(Ok(ref c)) if *c == credentials()),
+        "{step:?}"
+    );
+}
+
+#[test]
+fn time_out() {
+    let start = Instant::now();
+    let pairing = Pairing::new(start);
+
+    // Don't wait past the deadline
+    let now = start + Pairing::TIMEOUT - Duration::from_millis(500);
+    let step = pairing.handle(now, link_button_not_pressed());
+    assert!(
+        matches!(
+            step,
+            Step::Retry { after, .. } if after == Duration::from_millis(500)
+        ),
+        "{step:?}"
+    );
+
+    for now in [start + Pairing::TIMEOUT, start + Duration::from_secs(61)] {
+        let step = pairing.handle(now, link_button_not_pressed());
+        assert!(
+            matches!(step, Step::Done(Err(PairingError::TimedOut))),
+            "{step:?}"
+        );
+    }
+
+    // Pressed just in time, but the response came late
+    let step = pairing.handle(start + Duration::from_secs(61), Ok(credentials()));
+    assert!(matches!(step, Step::Done(Ok(_))), "{step:?}");
+}
+
+#[test]
+fn other_errors_are_final() {
+    let start = Instant::now();
+    let pairing = Pairing::new(start);
+    let errors = [
+        Error::Transport {
+            url: URL.to_string(),
+            description: "timed out".to_string(),
+        },
+        Error::Hue {
+            url: URL.to_string(),
+            error: HueError::new(7, "/devicetype", "invalid value"),
+        },
+    ];
+    for err in errors {
+        let step = pairing.handle(start, Err(err));
+        assert!(
+            matches!(step, Step::Done(Err(PairingError::Api(_)))),
+            "{step:?}"
+        );
+    }
+}
+
+#[test]
+fn devicetypes() {
+    assert_eq!(devicetype("MacBook-Pro.local"), "menhue#MacBook-Pro");
+    assert_eq!(devicetype("imac"), "menhue#imac");
+    assert_eq!(devicetype(""), "menhue");
+    assert_eq!(devicetype(".local"), "menhue");
+    // The bridge uses `#` to separate the application from the device
+    assert_eq!(devicetype("a#b"), "menhue#ab");
+    assert_eq!(devicetype("tab\there"), "menhue#tabhere");
+}
+
+#[test]
+fn truncate_devicetypes() {
+    // At most 19 characters after the `#`
+    assert_eq!(
+        devicetype("Annes-MacBook-Pro-2023.local"),
+        "menhue#Annes-MacBook-Pro-2"
+    );
+    assert_eq!(
+        devicetype("a".repeat(19).as_str()),
+        format!("menhue#{}", "a".repeat(19))
+    );
+
+    // Characters, not bytes
+    let device = devicetype("Jürgens-MacBook-Pröööö.local");
+    assert_eq!(device, "menhue#Jürgens-MacBook-Prö");
+    assert_eq!(device.chars().count(), "menhue#".len() + 19);
+    assert_eq!(
+        devicetype("日本語のコンピューター名前がとても長いです"),
+        "menhue#日本語のコンピューター名前がとても長い"
+    );
+}
+
+fn finish_connect(body: &str) -> (Client, Result<Credentials, Error>) {
+    let client = Client::new(Some("192.168.1.2".to_string()), None);
+    let url = client.connect_request("menhue#test").url;
+    let res = parse_response(&url, 200, body.as_bytes())
+        .and_then(|json| client.finish_connect(&url, &json));
+    (client, res)
+}
+
+#[test]
+fn store_credentials() {
+    let (client, res) = finish_connect(
+        r#"[{"success":{"username":"0123456789abcdefghijklmnopqrstuvwxyzABCD","clientkey":"0123456789ABCDEF0123456789ABCDEF"}}]"#,
+    );
+    assert_eq!(res.unwrap(), credentials());
+    assert_eq!(client.username(), Some(credentials().username));
+    assert_eq!(client.clientkey(), credentials().clientkey);
+
+    // Bridges before 1.22 don't generate client keys
+    let (client, res) =
+        finish_connect(r#"[{"success":{"username":"0123456789abcdefghijklmnopqrstuvwxyzABCD"}}]"#);
+    assert_eq!(res.unwrap().clientkey, None);
+    assert_eq!(client.username(), Some(credentials().username));
+    assert_eq!(client.clientkey(), None);
+}
+
+#[test]
+fn reject_responses_without_a_username() {
+    for body in [
+        r#"[{"success":{"clientkey":"0123456789ABCDEF0123456789ABCDEF"}}]"#,
+        r#"[{"success":{"username":5}}]"#,
+        r#"[{"success":{}}]"#,
+        "[]",
+    ] {
+        let (client, res) = finish_connect(body);
+        assert!(matches!(res, Err(Error::InvalidResponse { .. })), "{body}");
+        assert_eq!(client.username(), None, "{body}");
+        assert_eq!(client.clientkey(), None, "{body}");
+    }
+}
@@ -0,0 +1,127 @@
+//! Merging the changes made while a request is in flight.
+use menhue_core::light::{ColorMode, StateUpdate};
+use menhue_core::queue::{CommandQueue, Supersede};
+
+fn bri(bri: u8) -> StateUpdate {
+    StateUpdate {
+        bri: Some(bri),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn send_only_the_last_value() {
+    let queue = CommandQueue::new();
+    assert_eq!(queue.push("1", bri(10)), Some(bri(10)));
+    assert!(queue.is_busy("1"));
+
+    // While the first request is in flight
+    for value in 11..=20 {
+        assert_eq!(queue.push("1", bri(value)), None);
+    }
+    assert_eq!(queue.finish("1"), Some(bri(20)));
+    assert!(queue.is_busy("1"));
+
+    assert_eq!(queue.finish("1"), None);
+    assert!(!queue.is_busy("1"));
+    // Sent right away again
+    assert_eq!(queue.push("1", bri(30)), Some(bri(30)));
+}
+
+#[test]
+fn queue_per_key() {
+    let queue = CommandQueue::new();
+    assert_eq!(queue.push("1", bri(10)), Some(bri(10)));
+    assert_eq!(queue.push("2", bri(20)), Some(bri(20)));
+    assert_eq!(queue.push("1", bri(11)), None);
+    assert_eq!(queue.finish("2"), None);
+    assert_eq!(queue.finish("1"), Some(bri(11)));
+    // Finishing something that isn't in flight
+    assert_eq!(queue.finish("3"), None);
+}
+
+#[test]
+fn keep_what_the_newer_update_does_not_set() {
+    let older = StateUpdate {
+        on: Some(true),
+        bri: Some(100),
+        ct: Some(300),
+        transitiontime: Some(4),
+        ..Default::default()
+    };
+    let newer = StateUpdate {
+        bri: Some(200),
+        ..Default::default()
+    };
+    assert_eq!(
+        older.supersede(newer),
+        StateUpdate {
+            on: Some(true),
+            bri: Some(200),
+            ct: Some(300),
+            transitiontime: Some(4),
+            ..Default::default()
+        }
+    );
+
+    let older = StateUpdate {
+        on: Some(false),
+        ..Default::default()
+    };
+    let newer = StateUpdate {
+        on: Some(true),
+        hue: Some(1000),
+        ..Default::default()
+    };
+    let merged = older.supersede(newer);
+    assert_eq!(merged.on, Some(true));
+    assert_eq!(merged.hue, Some(1000));
+}
+
+#[test]
+fn merge_colors_in_the_same_mode() {
+    let older = StateUpdate {
+        hue: Some(1000),
+        sat: Some(100),
+        ..Default::default()
+    };
+    let newer = StateUpdate {
+        sat: Some(200),
+        ..Default::default()
+    };
+    let merged = older.supersede(newer);
+    assert_eq!(merged.hue, Some(1000));
+    assert_eq!(merged.sat, Some(200));
+}
+
+#[test]
+fn drop_colors_in_another_mode() {
+    let older = StateUpdate {
+        bri: Some(100),
+        xy: Some([0.3, 0.3]),
+        ..Default::default()
+    };
+    let newer = StateUpdate {
+        ct: Some(366),
+        ..Default::default()
+    };
+    // The bridge would apply xy over ct
+    let merged = older.supersede(newer);
+    assert_eq!(merged.xy, None);
+    assert_eq!(merged.ct, Some(366));
+    assert_eq!(merged.bri, Some(100));
+    assert_eq!(merged.colormode(), Some(ColorMode::Ct));
+
+    let older = StateUpdate {
+        hue: Some(1000),
+        sat: Some(100),
+        ..Default::default()
+    };
+    let newer = StateUpdate {
+        xy: Some([0.5, 0.4]),
+        ..Default::default()
+    };
+    let merged = older.supersede(newer);
+    assert_eq!((merged.hue, merged.sat), (None, None));
+    assert_eq!(merged.xy, Some([0.5, 0.4]));
+}
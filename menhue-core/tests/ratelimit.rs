@@ -0,0 +1,137 @@
+//! Releasing requests at the rate of their bucket, with synthetic times.
+use std::time::{Duration, Instant};
+
+use menhue_core::api::{Client, Method};
+use menhue_core::light::StateUpdate;
+use menhue_core::ratelimit::{Bucket, Limit, RateLimiter};
+use menhue_core::v2;
+use serde_json::json;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn buckets_of_requests() {
+    let client = Client::new(Some("192.168.1.2".to_string()), Some("user".to_string()));
+    let bucket = |method, path: &str| {
+        Bucket::for_request(&client.request(method, &client.authenticated_path(path), None))
+    };
+
+    assert_eq!(
+        Bucket::for_request(&client.set_light_state("1", &StateUpdate::default())),
+        Bucket::Light
+    );
+    assert_eq!(bucket(Method::Put, "/groups/1/action"), Bucket::Group);
+    assert_eq!(bucket(Method::Get, "/lights/1"), Bucket::Read);
+    assert_eq!(bucket(Method::Get, "/groups/1"), Bucket::Read);
+    // Changes to the bridge itself don't reach the lights
+    assert_eq!(
+        Bucket::for_request(&client.install_update_request()),
+        Bucket::Read
+    );
+    assert_eq!(
+        Bucket::for_request(&client.connect_request("menhue#test")),
+        Bucket::Read
+    );
+    assert_eq!(
+        Bucket::for_request(&client.delete_user_request("other")),
+        Bucket::Read
+    );
+
+    let v2 = v2::Client::new(client.clone());
+    let resource = |path: &str| {
+        Bucket::for_request(&v2.request(Method::Put, path, Some(&json!({ "on": { "on": true } }))))
+    };
+    assert_eq!(resource("/clip/v2/resource/light/abc"), Bucket::Light);
+    assert_eq!(
+        resource("/clip/v2/resource/grouped_light/abc"),
+        Bucket::Group
+    );
+    assert_eq!(Bucket::for_request(&v2.recall_scene("abc")), Bucket::Group);
+}
+
+#[test]
+fn burst() {
+    let limiter = RateLimiter::new();
+    let now = Instant::now();
+    for i in 0..7 {
+        limiter.push(Bucket::Read, i);
+    }
+
+    let ready = limiter.poll(now);
+    assert_eq!(ready.items, [0, 1, 2, 3, 4]);
+    // 10 per second
+    assert_eq!(ready.next_poll, Some(now + ms(100)));
+    assert_eq!(limiter.queue_depth(Bucket::Read), 2);
+}
+
+#[test]
+fn refill() {
+    let limiter = RateLimiter::new();
+    let now = Instant::now();
+    for i in 0..4 {
+        limiter.push(Bucket::Light, i);
+    }
+
+    assert_eq!(limiter.poll(now).items, [0]);
+    // Too early
+    let ready = limiter.poll(now + ms(50));
+    assert!(ready.items.is_empty());
+    assert_eq!(ready.next_poll, Some(now + ms(100)));
+    assert_eq!(limiter.poll(now + ms(100)).items, [1]);
+    // The burst is only one, so a longer wait doesn't release more
+    let ready = limiter.poll(now + ms(1000));
+    assert_eq!(ready.items, [2]);
+    assert_eq!(ready.next_poll, Some(now + ms(1100)));
+    let ready = limiter.poll(now + ms(1100));
+    assert_eq!(ready.items, [3]);
+    assert_eq!(ready.next_poll, None);
+}
+
+#[test]
+fn queue_order() {
+    let limiter = RateLimiter::with_limits(
+        Limit {
+            per_second: 1.0,
+            burst: 2.0,
+        },
+        Limit::GROUP,
+        Limit::READ,
+    );
+    let now = Instant::now();
+    for i in 0..3 {
+        limiter.push(Bucket::Light, i);
+    }
+    assert_eq!(limiter.poll(now).items, [0, 1]);
+
+    limiter.push(Bucket::Light, 3);
+    assert_eq!(limiter.poll(now + ms(500)).items, Vec::<i32>::new());
+    assert_eq!(limiter.poll(now + ms(1000)).items, [2]);
+    assert_eq!(limiter.poll(now + ms(2000)).items, [3]);
+}
+
+#[test]
+fn separate_buckets() {
+    let limiter = RateLimiter::new();
+    let now = Instant::now();
+    limiter.push(Bucket::Light, "light 1");
+    limiter.push(Bucket::Light, "light 2");
+    limiter.push(Bucket::Group, "group 1");
+    limiter.push(Bucket::Group, "group 2");
+    limiter.push(Bucket::Read, "read 1");
+    limiter.push(Bucket::Read, "read 2");
+
+    // Waiting lights don't hold up groups or reads
+    let ready = limiter.poll(now);
+    assert_eq!(ready.items, ["light 1", "group 1", "read 1", "read 2"]);
+    assert_eq!(ready.next_poll, Some(now + ms(100)));
+    assert_eq!(limiter.total_queue_depth(), 2);
+
+    let ready = limiter.poll(now + ms(100));
+    assert_eq!(ready.items, ["light 2"]);
+    // One group command per second
+    assert_eq!(ready.next_poll, Some(now + ms(1000)));
+    assert_eq!(limiter.poll(now + ms(1000)).items, ["group 2"]);
+    assert_eq!(limiter.total_queue_depth(), 0);
+}
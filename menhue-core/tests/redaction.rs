@@ -0,0 +1,87 @@
+//! Credentials must not end up in error messages, which are logged and
+//! shown in the menu.
+use menhue_core::api::{parse_response, Client, Error};
+use menhue_core::error::HueError;
+use menhue_core::fixture::redact_url;
+
+const USERNAME: &str = "0123456789abcdefghijklmnopqrstuvwxyzABCD";
+
+#[test]
+fn urls() {
+    assert_eq!(
+        redact_url(&format!("http://192.168.1.2/api/{USERNAME}/lights/1/state")),
+        "http://192.168.1.2/api/<username>/lights/1/state"
+    );
+    assert_eq!(
+        redact_url(&format!("https://bridge:8443/api/{USERNAME}")),
+        "https://bridge:8443/api/<username>"
+    );
+    // Other applications' usernames, when deleting them
+    assert_eq!(
+        redact_url(&format!(
+            "http://192.168.1.2/api/{USERNAME}/config/whitelist/other-user"
+        )),
+        "http://192.168.1.2/api/<username>/config/whitelist/<username>"
+    );
+    assert_eq!(
+        redact_url("http://192.168.1.2/api/config"),
+        "http://192.168.1.2/api/config"
+    );
+    assert_eq!(
+        redact_url("http://192.168.1.2/api"),
+        "http://192.168.1.2/api"
+    );
+    assert_eq!(
+        redact_url("https://192.168.1.2/clip/v2/resource/light"),
+        "https://192.168.1.2/clip/v2/resource/light"
+    );
+}
+
+#[test]
+fn errors() {
+    let client = Client::new(Some("192.168.1.2".to_string()), Some(USERNAME.to_string()));
+    let url = client.url(&client.authenticated_path("/lights"));
+
+    let errors = [
+        Error::Transport {
+            url: url.clone(),
+            description: "timed out".to_string(),
+        },
+        Error::Cancelled { url: url.clone() },
+        parse_response(&url, 503, b"").unwrap_err(),
+        parse_response(&url, 200, b"{").unwrap_err(),
+        parse_response(
+            &url,
+            200,
+            br#"[{"error":{"type":1,"address":"/","description":"unauthorized user"}}]"#,
+        )
+        .unwrap_err(),
+    ];
+    for err in errors {
+        let message = err.to_string();
+        assert!(!message.contains(USERNAME), "{message}");
+        assert!(message.contains("/api/<username>/lights"), "{message}");
+    }
+}
+
+#[test]
+fn whitelist_addresses() {
+    let error = HueError::new(
+        1,
+        format!("/config/whitelist/{USERNAME}"),
+        "unauthorized user",
+    );
+    assert_eq!(
+        error.to_string(),
+        "unauthorized user (type 1 at /config/whitelist/<username>)"
+    );
+}
+
+#[test]
+fn invalid_pairing_response() {
+    let client = Client::new(Some("192.168.1.2".to_string()), None);
+    let request = client.connect_request("menhue#test");
+    let json = serde_json::json!([{ "success": { "username": 5, "clientkey": "ABCDEF0123" } }]);
+    let err = client.finish_connect(&request.url, &json).unwrap_err();
+    assert!(!err.to_string().contains("ABCDEF0123"), "{err}");
+}
@@ -0,0 +1,309 @@
+//! Which failed requests are retried, and when, also through a [`Session`]
+//! against canned responses.
+use std::{cell::RefCell, collections::VecDeque, fmt, rc::Rc, thread, time::Duration};
+
+use menhue_core::api::{parse_update_response, Client, Error, Method, Request};
+use menhue_core::error::HueErrorKind;
+use menhue_core::retry::{transient_update_error, Decision, RetryPolicy};
+use menhue_core::session::Session;
+use menhue_core::transport::{Response, Transport};
+use serde_json::json;
+
+type Delayed = (Duration, Box<dyn FnOnce()>);
+
+/// Responds with the queued bodies or errors in order, and runs delayed
+/// work in [`Canned::run`].
+#[derive(Clone, Default)]
+struct Canned {
+    bodies: Rc<RefCell<VecDeque<Result<&'static str, Error>>>>,
+    sent: Rc<RefCell<Vec<Request>>>,
+    delayed: Rc<RefCell<VecDeque<Delayed>>>,
+}
+
+impl fmt::Debug for Canned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Canned")
+            .field("bodies", &self.bodies)
+            .field("sent", &self.sent)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Canned {
+    fn new(bodies: &[&'static str]) -> Self {
+        Self {
+            bodies: Rc::new(RefCell::new(bodies.iter().copied().map(Ok).collect())),
+            ..Default::default()
+        }
+    }
+
+    /// Fail the next request with `err`, before the queued bodies.
+    fn fail_with(self, err: Error) -> Self {
+        self.bodies.borrow_mut().push_front(Err(err));
+        self
+    }
+
+    /// Run the delayed work until there is none left.
+    fn run(&self) {
+        loop {
+            let Some((delay, f)) = self.delayed.borrow_mut().pop_front() else {
+                return;
+            };
+            thread::sleep(delay);
+            f();
+        }
+    }
+}
+
+impl Transport for Canned {
+    fn send(&self, request: Request, completion_handler: Box<dyn FnOnce(Result<Response, Error>)>) {
+        self.sent.borrow_mut().push(request);
+        let body = self
+            .bodies
+            .borrow_mut()
+            .pop_front()
+            .expect("unexpected request");
+        completion_handler(body.map(|body| Response {
+            status_code: 200,
+            headers: Vec::new(),
+            body: body.as_bytes().to_vec(),
+        }));
+    }
+
+    fn run_after(&self, delay: Duration, f: Box<dyn FnOnce()>) {
+        self.delayed.borrow_mut().push_back((delay, f));
+    }
+}
+
+const URL: &str = "http://192.168.1.2/api/user/lights/1/state";
+const BUSY: &str = r#"[{"error":{"type":901,"address":"/lights/1/state/bri","description":"Internal error, 404"}}]"#;
+const NOT_ON: &str = r#"[{"error":{"type":201,"address":"/lights/1/state/bri","description":"parameter, bri, is not modifiable. Device is set to off."}}]"#;
+const SUCCESS: &str = r#"[{"success":{"/lights/1/state/bri":100}}]"#;
+
+fn put_state(json: serde_json::Value) -> Request {
+    let client = Client::new(Some("192.168.1.2".to_string()), Some("user".to_string()));
+    client.request(
+        Method::Put,
+        &client.authenticated_path("/lights/1/state"),
+        Some(&json),
+    )
+}
+
+fn timed_out() -> Error {
+    Error::Transport {
+        url: URL.to_string(),
+        description: "timed out".to_string(),
+    }
+}
+
+fn transient(body: &str) -> Option<Error> {
+    let result = parse_update_response(URL, 200, body.as_bytes()).unwrap();
+    transient_update_error(URL, &result)
+}
+
+#[test]
+fn update_errors() {
+    assert!(matches!(transient(BUSY), Some(Error::Hue { .. })));
+    assert!(transient(NOT_ON).is_none());
+    assert!(transient(SUCCESS).is_none());
+    // Retrying wouldn't help the attribute that isn't busy
+    let mixed = format!(
+        "[{},{}]",
+        &BUSY[1..BUSY.len() - 1],
+        &NOT_ON[1..NOT_ON.len() - 1]
+    );
+    assert!(transient(&mixed).is_none());
+}
+
+#[test]
+fn backoff() {
+    let policy = RetryPolicy::default();
+    let request = put_state(json!({ "bri": 100 }));
+    let after = |attempts, jitter| policy.decide(&request, &timed_out(), attempts, jitter);
+
+    assert_eq!(
+        after(1, 1.0),
+        Decision::Retry {
+            after: Duration::from_millis(250)
+        }
+    );
+    assert_eq!(
+        after(2, 1.0),
+        Decision::Retry {
+            after: Duration::from_millis(500)
+        }
+    );
+    // Spread between half and all of the backoff
+    assert_eq!(
+        after(1, 0.0),
+        Decision::Retry {
+            after: Duration::from_millis(125)
+        }
+    );
+    assert_eq!(after(3, 1.0), Decision::GiveUp);
+
+    let policy = RetryPolicy {
+        max_attempts: 10,
+        ..policy
+    };
+    assert_eq!(
+        policy.decide(&request, &timed_out(), 8, 1.0),
+        Decision::Retry {
+            after: policy.max_delay
+        }
+    );
+}
+
+#[test]
+fn only_idempotent_requests() {
+    let policy = RetryPolicy::default();
+    let decide = |request: &Request| policy.decide(request, &timed_out(), 1, 0.5);
+
+    assert_ne!(decide(&put_state(json!({ "bri": 100 }))), Decision::GiveUp);
+    assert_eq!(
+        decide(&put_state(json!({ "bri_inc": 10 }))),
+        Decision::GiveUp
+    );
+    let client = Client::new(Some("192.168.1.2".to_string()), None);
+    assert_eq!(
+        decide(&client.connect_request("menhue#test")),
+        Decision::GiveUp
+    );
+}
+
+#[test]
+fn only_transient_errors() {
+    let policy = RetryPolicy::default();
+    let request = put_state(json!({ "bri": 100 }));
+    let not_on = parse_update_response(URL, 200, NOT_ON.as_bytes()).unwrap();
+    let err = Error::Hue {
+        url: URL.to_string(),
+        error: not_on.errors[0].clone(),
+    };
+    assert_eq!(policy.decide(&request, &err, 1, 0.5), Decision::GiveUp);
+    assert_eq!(
+        RetryPolicy::NONE.decide(&request, &timed_out(), 1, 0.5),
+        Decision::GiveUp
+    );
+}
+
+fn session(transport: &Canned) -> Session {
+    let client = Client::new(Some("192.168.1.2".to_string()), Some("user".to_string()));
+    Session::new(transport.clone(), client)
+}
+
+#[test]
+fn transient_update_errors_are_retried() {
+    let transport = Canned::new(&[BUSY, SUCCESS]);
+    let session = session(&transport);
+    let retries = Rc::new(RefCell::new(0));
+    session.set_on_retry({
+        let retries = retries.clone();
+        move |_, _| *retries.borrow_mut() += 1
+    });
+
+    let result = Rc::new(RefCell::new(None));
+    session.send_update(put_state(json!({ "bri": 100 })), {
+        let result = result.clone();
+        move |res| *result.borrow_mut() = Some(res)
+    });
+    transport.run();
+
+    let result = result.take().unwrap().unwrap();
+    assert!(result.is_ok(), "{result:?}");
+    assert_eq!(*retries.borrow(), 1);
+    assert_eq!(transport.sent.borrow().len(), 2);
+}
+
+#[test]
+fn transient_update_errors_are_reported_when_retries_run_out() {
+    let transport = Canned::new(&[BUSY, BUSY, BUSY]);
+    let session = session(&transport);
+
+    let result = Rc::new(RefCell::new(None));
+    session.send_update(put_state(json!({ "bri": 100 })), {
+        let result = result.clone();
+        move |res| *result.borrow_mut() = Some(res)
+    });
+    transport.run();
+
+    let result = result.take().unwrap().unwrap();
+    assert_eq!(result.errors[0].kind, HueErrorKind::InternalError);
+    assert_eq!(transport.sent.borrow().len(), 3);
+}
+
+#[test]
+fn other_update_errors_are_not_retried() {
+    let transport = Canned::new(&[NOT_ON]);
+    let session = session(&transport);
+
+    let result = Rc::new(RefCell::new(None));
+    session.send_update(put_state(json!({ "bri": 100 })), {
+        let result = result.clone();
+        move |res| *result.borrow_mut() = Some(res)
+    });
+    transport.run();
+
+    let result = result.take().unwrap().unwrap();
+    assert_eq!(result.errors[0].kind, HueErrorKind::DeviceOff);
+    assert_eq!(transport.sent.borrow().len(), 1);
+}
+
+#[test]
+fn relative_updates_are_not_retried() {
+    let transport = Canned::new(&[BUSY]);
+    let session = session(&transport);
+
+    let result = Rc::new(RefCell::new(None));
+    session.send_update(put_state(json!({ "bri_inc": 10 })), {
+        let result = result.clone();
+        move |res| *result.borrow_mut() = Some(res)
+    });
+    transport.run();
+
+    let result = result.take().unwrap().unwrap();
+    assert_eq!(result.errors[0].kind, HueErrorKind::InternalError);
+    assert_eq!(transport.sent.borrow().len(), 1);
+}
+
+#[test]
+fn dropped_connections_are_retried() {
+    let transport = Canned::new(&[SUCCESS]).fail_with(timed_out());
+    let session = session(&transport);
+
+    let result = Rc::new(RefCell::new(None));
+    session.send_update(put_state(json!({ "bri": 100 })), {
+        let result = result.clone();
+        move |res| *result.borrow_mut() = Some(res)
+    });
+    transport.run();
+
+    assert!(result.take().unwrap().unwrap().is_ok());
+    assert_eq!(transport.sent.borrow().len(), 2);
+}
+
+#[test]
+fn cancelled_requests_are_not_retried() {
+    let transport = Canned::new(&[SUCCESS]).fail_with(Error::Cancelled {
+        url: URL.to_string(),
+    });
+    let session = session(&transport);
+    let retries = Rc::new(RefCell::new(0));
+    session.set_on_retry({
+        let retries = retries.clone();
+        move |_, _| *retries.borrow_mut() += 1
+    });
+
+    let result = Rc::new(RefCell::new(None));
+    session.send_update(put_state(json!({ "bri": 100 })), {
+        let result = result.clone();
+        move |res| *result.borrow_mut() = Some(res)
+    });
+    transport.run();
+
+    // Not turned into a transport error on the way
+    let err = result.take().unwrap().unwrap_err();
+    assert!(matches!(err, Error::Cancelled { .. }), "{err:?}");
+    assert_eq!(*retries.borrow(), 0);
+    assert_eq!(transport.sent.borrow().len(), 1);
+}
@@ -0,0 +1,57 @@
+//! Parsing the bridges of `EXTRA_BRIDGES`.
+use menhue_core::settings::BridgeSettings;
+
+fn bridge(
+    host: &str,
+    username: Option<&str>,
+    clientkey: Option<&str>,
+    bridge_id: Option<&str>,
+) -> BridgeSettings {
+    BridgeSettings {
+        host: host.to_string(),
+        username: username.map(str::to_string),
+        clientkey: clientkey.map(str::to_string),
+        bridge_id: bridge_id.map(str::to_string),
+    }
+}
+
+#[test]
+fn username_and_host() {
+    assert_eq!(
+        BridgeSettings::parse_list("abc@192.168.1.2, def@bridge.local:8080"),
+        [
+            bridge("192.168.1.2", Some("abc"), None, None),
+            bridge("bridge.local:8080", Some("def"), None, None),
+        ]
+    );
+}
+
+#[test]
+fn clientkey_and_bridge_id() {
+    assert_eq!(
+        BridgeSettings::parse_list("abc:0123ABCD@192.168.1.2/001788fffe123456"),
+        [bridge(
+            "192.168.1.2",
+            Some("abc"),
+            Some("0123ABCD"),
+            Some("001788fffe123456")
+        )]
+    );
+}
+
+#[test]
+fn credentials_from_keychain() {
+    assert_eq!(
+        BridgeSettings::parse_list("192.168.1.2/001788fffe123456"),
+        [bridge("192.168.1.2", None, None, Some("001788fffe123456"))]
+    );
+}
+
+#[test]
+fn empty_entries() {
+    assert_eq!(BridgeSettings::parse_list(""), []);
+    assert_eq!(
+        BridgeSettings::parse_list("abc@,,@/id, def@192.168.1.3/"),
+        [bridge("192.168.1.3", Some("def"), None, None)]
+    );
+}
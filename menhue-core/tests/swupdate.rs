@@ -0,0 +1,172 @@
+//! Reading the firmware update state, and installing updates only when
+//! they're ready.
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use menhue_core::api::{Client, Error, Method, Request};
+use menhue_core::emulator::Bridge;
+use menhue_core::http::HttpClient;
+use menhue_core::session::Session;
+use menhue_core::swupdate::{parse_software_update, AutoInstall, UpdateState};
+use menhue_core::transport::{Response, Transport};
+use serde_json::json;
+
+const USERNAME: &str = "swupdate-test-user";
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Sends over HTTP, and keeps the requests.
+#[derive(Debug, Clone)]
+struct Recording {
+    http: HttpClient,
+    sent: Rc<RefCell<Vec<Request>>>,
+}
+
+impl Recording {
+    fn puts(&self) -> usize {
+        let sent = self.sent.borrow();
+        sent.iter()
+            .filter(|request| request.method == Method::Put)
+            .count()
+    }
+}
+
+impl Transport for Recording {
+    fn send(&self, request: Request, completion_handler: Box<dyn FnOnce(Result<Response, Error>)>) {
+        self.sent.borrow_mut().push(request.clone());
+        Transport::send(&self.http, request, completion_handler);
+    }
+
+    fn run_after(&self, delay: Duration, f: Box<dyn FnOnce()>) {
+        self.http.run_after(delay, f);
+    }
+}
+
+fn session(bridge: &Bridge) -> (Session, Recording) {
+    bridge.with_state(|state| state.add_user(USERNAME, "menhue#test"));
+    let transport = Recording {
+        http: HttpClient::new(TIMEOUT),
+        sent: Rc::default(),
+    };
+    let client = Client::new(Some(bridge.host()), Some(USERNAME.to_string()));
+    (Session::new(transport.clone(), client), transport)
+}
+
+/// Call `f` with a completion handler, and return what it was called with.
+fn wait<T: 'static>(f: impl FnOnce(Box<dyn FnOnce(T)>)) -> T {
+    let result = Rc::new(RefCell::new(None));
+    f(Box::new({
+        let result = result.clone();
+        move |res| *result.borrow_mut() = Some(res)
+    }));
+    let result = result.borrow_mut().take();
+    result.expect("completion handler was called")
+}
+
+#[test]
+fn parse_swupdate2() {
+    // From a BSB002 with a light that has an update downloaded
+    let config = json!({
+        "name": "Philips hue",
+        "swupdate2": {
+            "checkforupdate": false,
+            "lastchange": "2024-03-05T10:14:25",
+            "bridge": { "state": "noupdates", "lastinstall": "2024-02-27T14:05:43" },
+            "state": "anyreadytoinstall",
+            "autoinstall": { "updatetime": "T14:00:00", "on": true }
+        },
+        "apiversion": "1.62.0"
+    });
+    let update = parse_software_update(&config).unwrap();
+    assert_eq!(update.state, UpdateState::AnyReadyToInstall);
+    assert_eq!(update.bridge.state, UpdateState::NoUpdates);
+    assert_eq!(
+        update.bridge.lastinstall.as_deref(),
+        Some("2024-02-27T14:05:43")
+    );
+    assert_eq!(update.lastchange.as_deref(), Some("2024-03-05T10:14:25"));
+    assert!(!update.checkforupdate);
+    assert!(update.autoinstall.on);
+    assert_eq!(update.autoinstall.update_time(), Some((14, 0)));
+    assert!(update.is_ready());
+
+    // Newer states, and old bridges without `swupdate2`
+    let update = parse_software_update(&json!({ "swupdate2": { "state": "unknownstate" } }));
+    assert_eq!(update.unwrap().state, UpdateState::Unknown);
+    assert!(parse_software_update(&json!({ "name": "Philips hue" })).is_err());
+}
+
+#[test]
+fn ready_states() {
+    for state in [
+        UpdateState::ReadyToInstall,
+        UpdateState::AnyReadyToInstall,
+        UpdateState::AllReadyToInstall,
+    ] {
+        assert!(state.is_ready(), "{state:?}");
+    }
+    for state in [
+        UpdateState::NoUpdates,
+        UpdateState::Transferring,
+        UpdateState::Installing,
+        UpdateState::NotUpdatable,
+        UpdateState::Unknown,
+    ] {
+        assert!(!state.is_ready(), "{state:?}");
+    }
+}
+
+#[test]
+fn update_times() {
+    let update_time = |updatetime: Option<&str>| {
+        AutoInstall {
+            on: true,
+            updatetime: updatetime.map(str::to_string),
+        }
+        .update_time()
+    };
+    assert_eq!(update_time(Some("T14:00:00")), Some((14, 0)));
+    assert_eq!(update_time(Some("T00:30:00")), Some((0, 30)));
+    assert_eq!(update_time(Some("T23:59")), Some((23, 59)));
+
+    assert_eq!(update_time(Some("T24:00:00")), None);
+    assert_eq!(update_time(Some("T12:60:00")), None);
+    // Without the `T`
+    assert_eq!(update_time(Some("14:00")), None);
+    assert_eq!(update_time(Some("T14")), None);
+    assert_eq!(update_time(Some("")), None);
+    assert_eq!(update_time(None), None);
+}
+
+#[test]
+fn install_only_when_ready() {
+    let bridge = Bridge::start().unwrap();
+    let (session, transport) = session(&bridge);
+
+    assert!(!wait(|done| session.install_update(done)).unwrap());
+    assert_eq!(transport.puts(), 0);
+    let state = bridge.with_state(|state| state.swupdate["state"].clone());
+    assert_eq!(state, json!("noupdates"));
+
+    bridge.with_state(|state| state.swupdate["state"] = json!("anyreadytoinstall"));
+    assert!(wait(|done| session.install_update(done)).unwrap());
+    assert_eq!(transport.puts(), 1);
+    let update = wait(|done| session.software_update(done)).unwrap();
+    assert_eq!(update.state, UpdateState::Installing);
+
+    // Already installing
+    assert!(!wait(|done| session.install_update(done)).unwrap());
+    assert_eq!(transport.puts(), 1);
+}
+
+#[test]
+fn install_bridge_updates() {
+    let bridge = Bridge::start().unwrap();
+    let (session, transport) = session(&bridge);
+
+    bridge.with_state(|state| state.swupdate["bridge"]["state"] = json!("readytoinstall"));
+    assert!(wait(|done| session.install_update(done)).unwrap());
+    assert_eq!(transport.puts(), 1);
+    let update = wait(|done| session.software_update(done)).unwrap();
+    assert_eq!(update.bridge.state, UpdateState::Installing);
+}
@@ -0,0 +1,106 @@
+//! Verifying bridge certificates against roots and leaves generated on the
+//! fly, standing in for the Hue bridge root CA and a bridge.
+use std::time::SystemTime;
+
+use menhue_core::tls::{TrustError, TrustPolicy};
+use rcgen::{
+    BasicConstraints, CertificateParams, CertifiedIssuer, DnType, ExtendedKeyUsagePurpose, IsCa,
+    KeyPair, KeyUsagePurpose,
+};
+
+const BRIDGE_ID: &str = "001788FFFE123456";
+
+fn root(name: &str) -> CertifiedIssuer<'static, KeyPair> {
+    let mut params = CertificateParams::new(Vec::new()).unwrap();
+    params.distinguished_name.push(DnType::CommonName, name);
+    params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    params.key_usages = vec![KeyUsagePurpose::KeyCertSign, KeyUsagePurpose::CrlSign];
+    CertifiedIssuer::self_signed(params, KeyPair::generate().unwrap()).unwrap()
+}
+
+/// A bridge certificate, which has the bridge id as its common name.
+fn leaf(common_name: &str, root: &CertifiedIssuer<'_, KeyPair>) -> Vec<u8> {
+    let mut params = CertificateParams::new(Vec::new()).unwrap();
+    params
+        .distinguished_name
+        .push(DnType::CommonName, common_name);
+    params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ServerAuth];
+    let key = KeyPair::generate().unwrap();
+    params.signed_by(&key, root).unwrap().der().to_vec()
+}
+
+#[test]
+fn matching_common_name() {
+    let root = root("root-bridge");
+    let policy = TrustPolicy::with_roots(&[root.der()], BRIDGE_ID).unwrap();
+    // Bridges use the lowercase id in their certificate
+    let leaf = leaf(&BRIDGE_ID.to_ascii_lowercase(), &root);
+    assert_eq!(policy.verify(&leaf, &[], SystemTime::now()), Ok(()));
+}
+
+#[test]
+fn wrong_common_name() {
+    let root = root("root-bridge");
+    let policy = TrustPolicy::with_roots(&[root.der()], BRIDGE_ID).unwrap();
+    let leaf = leaf("001788fffe654321", &root);
+    assert_eq!(
+        policy.verify(&leaf, &[], SystemTime::now()),
+        Err(TrustError::WrongBridge {
+            expected: BRIDGE_ID.to_string(),
+            found: Some("001788fffe654321".to_string()),
+        })
+    );
+}
+
+#[test]
+fn untrusted_root() {
+    let trusted = root("root-bridge");
+    let other = root("other-root");
+    let policy = TrustPolicy::with_roots(&[trusted.der()], BRIDGE_ID).unwrap();
+    let leaf = leaf(&BRIDGE_ID.to_ascii_lowercase(), &other);
+    assert_eq!(
+        policy.verify(&leaf, &[], SystemTime::now()),
+        Err(TrustError::Certificate(webpki::Error::UnknownIssuer))
+    );
+}
+
+#[test]
+fn impostor_root_with_the_same_name() {
+    let trusted = root("root-bridge");
+    let impostor = root("root-bridge");
+    let policy = TrustPolicy::with_roots(&[trusted.der()], BRIDGE_ID).unwrap();
+    let leaf = leaf(&BRIDGE_ID.to_ascii_lowercase(), &impostor);
+    assert_eq!(
+        policy.verify(&leaf, &[], SystemTime::now()),
+        Err(TrustError::Certificate(
+            webpki::Error::InvalidSignatureForPublicKey
+        ))
+    );
+}
+
+#[test]
+fn any_of_several_roots() {
+    let first = root("first");
+    let second = root("second");
+    let policy = TrustPolicy::with_roots(&[first.der(), second.der()], BRIDGE_ID).unwrap();
+    let leaf = leaf(&BRIDGE_ID.to_ascii_lowercase(), &second);
+    assert_eq!(policy.verify(&leaf, &[], SystemTime::now()), Ok(()));
+}
+
+#[test]
+fn generated_leaf_is_not_a_hue_bridge() {
+    let root = root("root-bridge");
+    let leaf = leaf(&BRIDGE_ID.to_ascii_lowercase(), &root);
+    assert_eq!(
+        TrustPolicy::new(BRIDGE_ID).verify(&leaf, &[], SystemTime::now()),
+        Err(TrustError::Certificate(webpki::Error::UnknownIssuer))
+    );
+}
+
+#[test]
+fn invalid_root() {
+    assert!(matches!(
+        TrustPolicy::with_roots(&[b"not a certificate"], BRIDGE_ID),
+        Err(TrustError::Certificate(_))
+    ));
+}
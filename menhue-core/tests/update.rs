@@ -0,0 +1,129 @@
+//! The outcome of requests that change several attributes at once.
+use menhue_core::api::{parse_update_response, Error, Success, UpdateResult};
+use menhue_core::error::{HttpErrorBody, HueError, HueErrorKind};
+use serde_json::json;
+
+const URL: &str = "http://192.168.1.2/api/user/lights/1/state";
+
+fn parse(body: &str) -> Result<UpdateResult, Error> {
+    parse_update_response(URL, 200, body.as_bytes())
+}
+
+#[test]
+fn all_succeeded() {
+    let result = parse(
+        r#"[{"success":{"/lights/1/state/on":true}},{"success":{"/lights/1/state/bri":200}}]"#,
+    )
+    .unwrap();
+    assert!(result.is_ok());
+    assert_eq!(
+        result.successes,
+        [
+            Success {
+                address: "/lights/1/state/on".to_string(),
+                value: json!(true),
+            },
+            Success {
+                address: "/lights/1/state/bri".to_string(),
+                value: json!(200),
+            },
+        ]
+    );
+    assert_eq!(result.success("/lights/1/state/bri"), Some(&json!(200)));
+    assert_eq!(result.success("/lights/1/state/ct"), None);
+}
+
+#[test]
+fn mixed_successes_and_errors() {
+    // Setting the color of a light that is off
+    let result = parse(
+        r#"[
+            {"success":{"/lights/1/state/transitiontime":4}},
+            {"error":{"type":201,"address":"/lights/1/state/xy","description":"parameter, xy, is not modifiable. Device is set to off."}},
+            {"error":{"type":201,"address":"/lights/1/state/bri","description":"parameter, bri, is not modifiable. Device is set to off."}}
+        ]"#,
+    )
+    .unwrap();
+    assert!(!result.is_ok());
+    assert_eq!(result.successes.len(), 1);
+    assert_eq!(result.errors.len(), 2);
+    assert_eq!(
+        result.error("/lights/1/state/xy").map(|error| error.kind),
+        Some(HueErrorKind::DeviceOff)
+    );
+    assert_eq!(result.error("/lights/1/state/transitiontime"), None);
+    assert_eq!(
+        result.success("/lights/1/state/transitiontime"),
+        Some(&json!(4))
+    );
+}
+
+#[test]
+fn several_attributes_in_one_success() {
+    let result =
+        parse(r#"[{"success":{"/lights/1/state/on":true,"/lights/1/state/bri":1}}]"#).unwrap();
+    assert_eq!(result.successes.len(), 2);
+    assert!(result.is_ok());
+}
+
+#[test]
+fn single_object() {
+    let result = parse(
+        r#"{"error":{"type":3,"address":"/lights/9/state","description":"resource, /lights/9/state, not available"}}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        result.errors,
+        [HueError::new(
+            3,
+            "/lights/9/state",
+            "resource, /lights/9/state, not available"
+        )]
+    );
+    assert!(result.successes.is_empty());
+}
+
+#[test]
+fn empty_array() {
+    let result = parse("[]").unwrap();
+    assert_eq!(result, UpdateResult::default());
+    // Nothing failed
+    assert!(result.is_ok());
+}
+
+#[test]
+fn invalid_responses() {
+    // Neither an array nor an object
+    for body in ["5", r#""ok""#, "null", "true"] {
+        assert!(
+            matches!(parse(body), Err(Error::InvalidResponse { .. })),
+            "{body}"
+        );
+    }
+    // Items that are neither a success nor an error
+    for body in [
+        "[5]",
+        r#"[{"unknown":{}}]"#,
+        r#"[{"success":true}]"#,
+        r#"[{"error":"busy"}]"#,
+    ] {
+        assert!(
+            matches!(parse(body), Err(Error::InvalidResponse { .. })),
+            "{body}"
+        );
+    }
+    assert!(matches!(parse("[{"), Err(Error::Json { .. })));
+}
+
+#[test]
+fn http_errors() {
+    let err = parse_update_response(URL, 503, b"").unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Http {
+            status_code: 503,
+            body: HttpErrorBody::Empty,
+            ..
+        }
+    ));
+}
@@ -0,0 +1,246 @@
+//! Fetching v2 resources through a [`Session`], against responses recorded
+//! from a bridge.
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use menhue_core::api::{Client, Error};
+use menhue_core::error::HttpErrorBody;
+use menhue_core::fixture::{Exchange, Replay};
+use menhue_core::session::Session;
+use menhue_core::v2::{
+    self, DimmingUpdate, GroupedLight, IdMap, LightUpdate, On, ResourceType, UnknownResource,
+};
+use serde_json::{json, Value};
+
+const LIGHT: &str = "3ab27084-d02f-44b9-bd56-70ea41163cb6";
+const GROUPED_LIGHT: &str = "f2b6bc6e-a0a4-4b2b-a5b6-4c8a8b6e3f18";
+
+/// `GET /clip/v2/resource/light` with one color light.
+fn lights() -> Value {
+    json!({
+        "errors": [],
+        "data": [{
+            "id": LIGHT,
+            "id_v1": "/lights/1",
+            "owner": { "rid": "8cb0d4b1-5fd0-4d6e-9b39-f0a4b9b5d1d2", "rtype": "device" },
+            "metadata": { "name": "Hue color lamp 1", "archetype": "sultan_bulb" },
+            "identify": {},
+            "on": { "on": true },
+            "dimming": { "brightness": 39.37, "min_dim_level": 0.2 },
+            "dimming_delta": {},
+            "color_temperature": {
+                "mirek": null,
+                "mirek_valid": false,
+                "mirek_schema": { "mirek_minimum": 153, "mirek_maximum": 500 }
+            },
+            "color_temperature_delta": {},
+            "color": {
+                "xy": { "x": 0.4573, "y": 0.41 },
+                "gamut": {
+                    "red": { "x": 0.6915, "y": 0.3083 },
+                    "green": { "x": 0.17, "y": 0.7 },
+                    "blue": { "x": 0.1532, "y": 0.0475 }
+                },
+                "gamut_type": "C"
+            },
+            "dynamics": { "status": "none", "speed": 0.0, "speed_valid": false },
+            "mode": "normal",
+            "type": "light"
+        }]
+    })
+}
+
+/// `GET /clip/v2/resource/grouped_light` with a room and the bridge home,
+/// which has no v1 id.
+fn grouped_lights() -> Value {
+    json!({
+        "errors": [],
+        "data": [
+            {
+                "id": GROUPED_LIGHT,
+                "id_v1": "/groups/1",
+                "owner": { "rid": "6d1a4a36-2c7e-4ad7-9b5f-c6cbb1cde6f4", "rtype": "room" },
+                "on": { "on": true },
+                "dimming": { "brightness": 39.37 },
+                "alert": { "action_values": ["breathe"] },
+                "type": "grouped_light"
+            },
+            {
+                "id": "0c4b1d8e-6f0e-4bd5-9d4f-2a3c5a7e1b90",
+                "id_v1": "/groups/0",
+                "owner": { "rid": "e3a7c1f0-1f0a-4b0e-8a4e-2b8f4c6d5a11", "rtype": "bridge_home" },
+                "on": { "on": false },
+                "type": "grouped_light"
+            }
+        ]
+    })
+}
+
+fn exchange(method: &str, path: &str, request: Option<Value>, response: Value) -> Exchange {
+    Exchange {
+        method: method.to_string(),
+        path: path.to_string(),
+        request,
+        status_code: 200,
+        response: Some(response),
+        response_text: None,
+    }
+}
+
+fn session(exchanges: Vec<Exchange>) -> Session {
+    let client = Client::new(Some("192.168.1.2".to_string()), Some("user".to_string()));
+    Session::new(Replay::new(exchanges), client)
+}
+
+/// Call `f` with a completion handler, and return what it was called with.
+///
+/// [`Replay`] responds right away, so the handler has been called by the
+/// time `f` returns.
+fn wait<T: 'static>(f: impl FnOnce(Box<dyn FnOnce(T)>)) -> T {
+    let result = Rc::new(RefCell::new(None));
+    f(Box::new({
+        let result = result.clone();
+        move |res| *result.borrow_mut() = Some(res)
+    }));
+    let result = result.borrow_mut().take();
+    result.expect("completion handler was called")
+}
+
+fn id_map() -> IdMap {
+    let session = session(vec![
+        exchange("GET", "/clip/v2/resource/light", None, lights()),
+        exchange(
+            "GET",
+            "/clip/v2/resource/grouped_light",
+            None,
+            grouped_lights(),
+        ),
+    ]);
+    wait(|done| session.id_map(done)).unwrap()
+}
+
+#[test]
+fn resources() {
+    let session = session(vec![exchange(
+        "GET",
+        "/clip/v2/resource/light",
+        None,
+        lights(),
+    )]);
+    let lights: Vec<v2::Light> = wait(|done| session.resources(done)).unwrap();
+    let [light] = &*lights else {
+        panic!("expected one light: {lights:?}");
+    };
+    assert_eq!(light.id, LIGHT);
+    assert_eq!(light.metadata.name, "Hue color lamp 1");
+    assert_eq!(light.on, On { on: true });
+    assert_eq!(light.dimming.unwrap().brightness, 39.37);
+    assert_eq!(light.color_temperature.unwrap().mirek, None);
+    assert_eq!(light.color.unwrap().xy.x, 0.4573);
+
+    let session = self::session(vec![exchange(
+        "GET",
+        "/clip/v2/resource/grouped_light",
+        None,
+        grouped_lights(),
+    )]);
+    let grouped_lights: Vec<GroupedLight> = wait(|done| session.resources(done)).unwrap();
+    assert_eq!(grouped_lights.len(), 2);
+    assert_eq!(grouped_lights[1].dimming, None);
+}
+
+#[test]
+fn resources_with_an_unknown_application_key() {
+    let session = session(vec![Exchange {
+        status_code: 403,
+        ..exchange(
+            "GET",
+            "/clip/v2/resource/light",
+            None,
+            json!({ "errors": [{ "description": "unauthorized user" }], "data": [] }),
+        )
+    }]);
+    let err = wait(|done| session.resources::<v2::Light>(done)).unwrap_err();
+    assert!(err.is_unauthorized(), "{err:?}");
+    assert!(matches!(
+        err,
+        Error::Http { body: HttpErrorBody::V2(ref errors), .. } if errors == &["unauthorized user"]
+    ));
+}
+
+#[test]
+fn translate_ids() {
+    let ids = id_map();
+    assert_eq!(ids.light("1"), Some(LIGHT));
+    assert_eq!(ids.grouped_light("1"), Some(GROUPED_LIGHT));
+    assert!(ids.grouped_light("0").is_some());
+    assert_eq!(ids.v1_id(LIGHT), Some("/lights/1"));
+    assert_eq!(ids.v1_id(GROUPED_LIGHT), Some("/groups/1"));
+    // Group 1 is both a room and a grouped light
+    assert_eq!(ids.v2_id("/groups/1", ResourceType::Room), None);
+    assert_eq!(ids.light("2"), None);
+}
+
+#[test]
+fn update_light() {
+    let session = session(vec![exchange(
+        "PUT",
+        &format!("/clip/v2/resource/light/{LIGHT}"),
+        Some(json!({ "on": { "on": true }, "dimming": { "brightness": 50.0 } })),
+        json!({ "errors": [], "data": [{ "rid": LIGHT, "rtype": "light" }] }),
+    )]);
+    let update = LightUpdate {
+        on: Some(On { on: true }),
+        dimming: Some(DimmingUpdate { brightness: 50.0 }),
+        ..Default::default()
+    };
+    let response = wait(|done| {
+        session
+            .update_light_v2(&id_map(), "1", &update, done)
+            .unwrap()
+    })
+    .unwrap();
+    assert_eq!(response.data[0].rid, LIGHT);
+    assert_eq!(response.data[0].rtype, ResourceType::Light);
+    assert!(response.errors.is_empty());
+}
+
+#[test]
+fn update_light_partially() {
+    let session = session(vec![exchange(
+        "PUT",
+        &format!("/clip/v2/resource/light/{LIGHT}"),
+        None,
+        json!({
+            "errors": [{ "description": "device (light) has communication issues, command (.on) may not have effect" }],
+            "data": [{ "rid": LIGHT, "rtype": "light" }]
+        }),
+    )]);
+    let update = LightUpdate {
+        on: Some(On { on: false }),
+        ..Default::default()
+    };
+    let response = wait(|done| {
+        session
+            .update_light_v2(&id_map(), "1", &update, done)
+            .unwrap()
+    })
+    .unwrap();
+    assert_eq!(response.data.len(), 1);
+    assert_eq!(response.errors.len(), 1);
+}
+
+#[test]
+fn update_unknown_light() {
+    // Nothing is sent, which the replay would reject
+    let session = session(Vec::new());
+    let res = session.update_light_v2(&id_map(), "7", &LightUpdate::default(), |_| {
+        panic!("no request should have been sent")
+    });
+    assert_eq!(
+        res,
+        Err(UnknownResource {
+            id_v1: "/lights/7".to_string()
+        })
+    );
+}
@@ -0,0 +1,123 @@
+//! Deleting users against the emulated bridge, and building the request
+//! for the remote API.
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use menhue_core::api::{Client, Error, Method};
+use menhue_core::emulator::Bridge;
+use menhue_core::error::HueError;
+use menhue_core::http::HttpClient;
+use menhue_core::session::Session;
+use menhue_core::whitelist::{self, DeleteError, Protected, User};
+
+const USERNAME: &str = "emulator-test-user";
+
+fn hue_error(code: u16, address: &str) -> Error {
+    Error::Hue {
+        url: format!("http://192.168.1.2/api/{USERNAME}{address}"),
+        error: HueError::new(code, address, "unauthorized user"),
+    }
+}
+
+#[test]
+fn refused_deletion_needs_the_remote_api() {
+    let address = "/config/whitelist/other";
+    assert!(matches!(
+        DeleteError::from(hue_error(1, address)),
+        DeleteError::NeedsRemote(_)
+    ));
+    assert!(matches!(
+        DeleteError::from(hue_error(4, address)),
+        DeleteError::NeedsRemote(_)
+    ));
+    // Not about deleting the user
+    assert!(matches!(
+        DeleteError::from(hue_error(1, "/lights")),
+        DeleteError::Api(_)
+    ));
+    assert!(matches!(
+        DeleteError::from(hue_error(3, address)),
+        DeleteError::Api(_)
+    ));
+    assert!(matches!(
+        DeleteError::from(Error::Cancelled {
+            url: "http://192.168.1.2/api".to_string()
+        }),
+        DeleteError::Api(_)
+    ));
+}
+
+fn setup(local_user_deletion: bool) -> (Bridge, Session, Vec<User>) {
+    let bridge = Bridge::start().unwrap();
+    bridge.with_state(|state| {
+        state.local_user_deletion = local_user_deletion;
+        state.add_user(USERNAME, "menhue#laptop");
+        state.add_user("same-computer", "menhue#laptop");
+        state.add_user("other-bridge", "menhue#desktop");
+        state.add_user("stale", "menhue#old");
+    });
+    let client = Client::new(Some(bridge.host()), Some(USERNAME.to_string()));
+    let session = Session::new(HttpClient::new(Duration::from_secs(2)), client);
+    let users = wait(|done| session.whitelist(done)).unwrap();
+    (bridge, session, users)
+}
+
+fn wait<T: 'static>(f: impl FnOnce(Box<dyn FnOnce(T)>)) -> T {
+    let result = Rc::new(RefCell::new(None));
+    f(Box::new({
+        let result = result.clone();
+        move |res| *result.borrow_mut() = Some(res)
+    }));
+    let result = result.borrow_mut().take();
+    result.expect("completion handler was called")
+}
+
+fn user<'a>(users: &'a [User], username: &str) -> &'a User {
+    users.iter().find(|user| user.username == username).unwrap()
+}
+
+#[test]
+fn protected_users() {
+    let (bridge, session, users) = setup(true);
+    let protected = Protected {
+        usernames: vec!["other-bridge".to_string()],
+        devicetypes: vec!["menhue#laptop".to_string()],
+    };
+
+    for username in [USERNAME, "same-computer", "other-bridge"] {
+        let res = wait(|done| session.delete_user(user(&users, username), &protected, done));
+        assert!(
+            matches!(&res, Err(DeleteError::Protected { username: protected }) if protected == username),
+            "{res:?}"
+        );
+    }
+    // Even without a protected set
+    let res = wait(|done| session.delete_user(user(&users, USERNAME), &Protected::default(), done));
+    assert!(matches!(res, Err(DeleteError::Protected { .. })));
+
+    wait(|done| session.delete_user(user(&users, "stale"), &protected, done)).unwrap();
+    let remaining: Vec<String> = bridge.with_state(|state| state.users.keys().cloned().collect());
+    assert_eq!(remaining, [USERNAME, "other-bridge", "same-computer"]);
+}
+
+#[test]
+fn bridge_refuses_deletion() {
+    let (_bridge, session, users) = setup(false);
+    let res = wait(|done| session.delete_user(user(&users, "stale"), &Protected::default(), done));
+    assert!(matches!(res, Err(DeleteError::NeedsRemote(_))), "{res:?}");
+}
+
+#[test]
+fn remote_delete_request() {
+    let request = whitelist::remote_delete_request("token", "remote-user", "other");
+    assert_eq!(request.method, Method::Delete);
+    assert_eq!(
+        request.url,
+        "https://api.meethue.com/route/api/remote-user/config/whitelist/other"
+    );
+    assert_eq!(
+        request.headers,
+        [("Authorization".to_string(), "Bearer token".to_string())]
+    );
+}
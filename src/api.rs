@@ -1,101 +1,185 @@
-use std::{
-    cell::{Cell, RefCell},
-    rc::Rc,
-};
+use std::cell::Cell;
+use std::ops::Deref;
+use std::ptr;
+use std::rc::Rc;
+use std::time::{Duration, SystemTime};
 
-use block2::RcBlock;
-use objc2::{rc::Retained, runtime::AnyObject, Message};
+use block2::{DynBlock, RcBlock};
+use dispatch2::MainThreadBound;
+use menhue_core::api::{Client, Error, Request};
+use menhue_core::fixture::{Recorder, Replay};
+use menhue_core::session as core;
+use menhue_core::settings::Settings;
+use menhue_core::trace::{RotatingLog, Tracer};
+use menhue_core::transport::{Response, Transport};
+use objc2::rc::Retained;
+use objc2::runtime::ProtocolObject;
+use objc2::{define_class, msg_send, AllocAnyThread, ClassType, DeclaredClass};
+use objc2_core_foundation::{CFArray, CFRetained};
 use objc2_foundation::{
-    ns_string, MainThreadMarker, NSArray, NSCopying, NSData, NSDictionary, NSError,
-    NSHTTPURLResponse, NSJSONReadingOptions, NSJSONSerialization, NSJSONWritingOptions,
-    NSLocalizedDescriptionKey, NSMutableArray, NSMutableURLRequest, NSNumber, NSOperationQueue,
-    NSString, NSURLComponents, NSURLErrorKey, NSURLRequestCachePolicy,
-    NSURLRequestNetworkServiceType, NSURLResponse, NSURLSession, NSURLSessionConfiguration,
-    NSURLSessionTask,
+    ns_string, MainThreadMarker, NSData, NSError, NSHTTPURLResponse, NSMutableURLRequest, NSObject,
+    NSObjectProtocol, NSOperationQueue, NSString, NSTimer, NSURLAuthenticationChallenge,
+    NSURLAuthenticationMethodServerTrust, NSURLCredential, NSURLErrorCancelled,
+    NSURLRequestCachePolicy, NSURLRequestNetworkServiceType, NSURLResponse, NSURLSession,
+    NSURLSessionAuthChallengeDisposition, NSURLSessionConfiguration, NSURLSessionDelegate, NSURL,
 };
+use objc2_security::{SecCertificate, SecTrust};
 
-pub const HTTP_STATUS_CODE_DOMAIN: &'static str = "HTTPCodeError";
-pub const HUE_API_ERROR: &'static str = "HueAPIError";
+define_class!(
+    /// Verifies the bridge's certificate, see [`handle_challenge`].
+    #[unsafe(super(NSObject))]
+    #[name = "SessionDelegate"]
+    #[ivars = MainThreadBound<Client>]
+    struct SessionDelegate;
 
-#[derive(Debug, Clone)]
-pub struct Session {
+    unsafe impl NSObjectProtocol for SessionDelegate {}
+
+    unsafe impl NSURLSessionDelegate for SessionDelegate {
+        #[unsafe(method(URLSession:didReceiveChallenge:completionHandler:))]
+        fn _did_receive_challenge(
+            &self,
+            _session: &NSURLSession,
+            challenge: &NSURLAuthenticationChallenge,
+            completion_handler: &DynBlock<
+                dyn Fn(NSURLSessionAuthChallengeDisposition, *mut NSURLCredential),
+            >,
+        ) {
+            let mtm = MainThreadMarker::new().expect("session delegate called on the main thread");
+            handle_challenge(self.ivars().get(mtm), challenge, completion_handler);
+        }
+    }
+);
+
+impl SessionDelegate {
+    fn new(mtm: MainThreadMarker, client: Client) -> Retained<Self> {
+        let this = Self::alloc().set_ivars(MainThreadBound::new(client, mtm));
+        unsafe { msg_send![super(this), init] }
+    }
+}
+
+/// Answer a session-level authentication challenge.
+///
+/// The session's delegate queue must be the main queue.
+pub fn handle_challenge(
+    client: &Client,
+    challenge: &NSURLAuthenticationChallenge,
+    completion_handler: &DynBlock<
+        dyn Fn(NSURLSessionAuthChallengeDisposition, *mut NSURLCredential),
+    >,
+) {
+    let protection_space = challenge.protectionSpace();
+    if !protection_space
+        .authenticationMethod()
+        .isEqualToString(unsafe { NSURLAuthenticationMethodServerTrust })
+    {
+        completion_handler.call((
+            NSURLSessionAuthChallengeDisposition::PerformDefaultHandling,
+            ptr::null_mut(),
+        ));
+        return;
+    }
+
+    let trust: *mut SecTrust = unsafe { msg_send![&protection_space, serverTrust] };
+    let trust = unsafe { trust.as_ref() }.expect("server trust challenge without a trust object");
+    match verify_trust(client, trust) {
+        Ok(()) => {
+            let credential: Retained<NSURLCredential> =
+                unsafe { msg_send![NSURLCredential::class(), credentialForTrust: trust] };
+            completion_handler.call((
+                NSURLSessionAuthChallengeDisposition::UseCredential,
+                Retained::as_ptr(&credential).cast_mut(),
+            ));
+        }
+        Err(err) => {
+            eprintln!("not trusting {}: {err}", protection_space.host());
+            completion_handler.call((
+                NSURLSessionAuthChallengeDisposition::CancelAuthenticationChallenge,
+                ptr::null_mut(),
+            ));
+        }
+    }
+}
+
+/// Verify the bridge's certificate with the policy from
+/// [`Client::trust_policy`], instead of the system's trust store.
+fn verify_trust(client: &Client, trust: &SecTrust) -> Result<(), String> {
+    let policy = client.trust_policy().ok_or("the bridge id is not known")?;
+
+    let chain = unsafe { trust.certificate_chain() }.ok_or("no certificate chain")?;
+    // SAFETY: The chain contains `SecCertificateRef`s.
+    let chain = unsafe { CFRetained::cast_unchecked::<CFArray<SecCertificate>>(chain) };
+    let chain: Vec<Vec<u8>> = chain
+        .iter()
+        .map(|cert| unsafe { cert.data() }.to_vec())
+        .collect();
+    let (end_entity, intermediates) = chain.split_first().ok_or("empty certificate chain")?;
+    let intermediates: Vec<&[u8]> = intermediates.iter().map(Vec::as_slice).collect();
+
+    policy
+        .verify(end_entity, &intermediates, SystemTime::now())
+        .map_err(|err| err.to_string())
+}
+
+/// Convert a request from the core.
+pub fn ns_request(request: &Request) -> Retained<NSMutableURLRequest> {
+    let url = NSURL::URLWithString(&NSString::from_str(&request.url))
+        .expect("building NSURL from string");
+
+    let body = request.body.as_deref().map(NSData::with_bytes);
+
+    let ns_request = NSMutableURLRequest::requestWithURL(&url);
+    ns_request.setCachePolicy(NSURLRequestCachePolicy::ReloadIgnoringCacheData);
+    ns_request.setHTTPMethod(&NSString::from_str(request.method.as_str()));
+    ns_request.setHTTPBody(body.as_deref());
+    for (field, value) in &request.headers {
+        ns_request
+            .addValue_forHTTPHeaderField(&NSString::from_str(value), &NSString::from_str(field));
+    }
+    ns_request
+}
+
+/// Sends requests with an `NSURLSession`, calling back on the main thread.
+#[derive(Debug)]
+pub struct UrlSessionTransport {
     url_session: Retained<NSURLSession>,
-    host: Rc<RefCell<Option<Retained<NSString>>>>,
-    username: Rc<RefCell<Option<Retained<NSString>>>>,
 }
 
-impl Session {
-    pub fn new(
-        _mtm: MainThreadMarker,
-        host: Rc<RefCell<Option<Retained<NSString>>>>,
-        username: Rc<RefCell<Option<Retained<NSString>>>>,
-    ) -> Self {
+impl UrlSessionTransport {
+    pub fn new(mtm: MainThreadMarker, client: Client) -> Self {
         let config = NSURLSessionConfiguration::ephemeralSessionConfiguration();
         // It makes no sense to try to configure Hue lights on cellular networks
         config.setAllowsCellularAccess(false);
         // Time out after 5 seconds; the Hue bridge is on the local network
         config.setTimeoutIntervalForRequest(5.0);
-        // TODO
+        // The bridge handles requests one at a time anyway; updates to the
+        // same light are coalesced in `Session::update_light` so they
+        // don't queue up
         config.setHTTPMaximumConnectionsPerHost(1);
         // We only make requests on direct user action
         config.setNetworkServiceType(
             NSURLRequestNetworkServiceType::NetworkServiceTypeResponsiveData,
         );
+        // Retained by the session until it is invalidated
+        let delegate = SessionDelegate::new(mtm, client);
         let url_session = unsafe {
             NSURLSession::sessionWithConfiguration_delegate_delegateQueue(
                 &config,
-                None,
+                Some(ProtocolObject::from_ref(&*delegate)),
                 // Ensure that all operations are executed on the main thread
                 Some(&NSOperationQueue::mainQueue()),
             )
         };
         url_session.setSessionDescription(Some(ns_string!("bridge connection")));
 
-        Self {
-            url_session,
-            host,
-            username,
-        }
+        Self { url_session }
     }
+}
 
-    pub fn request(
-        &self,
-        method: &NSString,
-        path: &NSString,
-        json_object: Option<&AnyObject>,
-        completion_handler: impl FnOnce(Result<Retained<AnyObject>, Retained<NSError>>) + 'static,
-    ) -> Retained<NSURLSessionTask> {
-        let components = NSURLComponents::new();
-        components.setHost(Some(
-            self.host
-                .borrow()
-                .as_ref()
-                .expect("host must be set before making URL request"),
-        ));
-        components.setPath(Some(path));
-        // TODO: Use encryption from API V2
-        components.setScheme(Some(ns_string!("http")));
-        let url = components.URL().expect("building NSURL from components");
-
-        let body = json_object.map(|json_object| {
-            unsafe {
-                NSJSONSerialization::dataWithJSONObject_options_error(
-                    json_object,
-                    NSJSONWritingOptions::PrettyPrinted,
-                )
-            }
-            .expect("json writing")
-        });
-
-        let request = NSMutableURLRequest::requestWithURL(&url);
-        request.setCachePolicy(NSURLRequestCachePolicy::ReloadIgnoringCacheData);
-        request.setHTTPMethod(method);
-        request.setHTTPBody(body.as_deref());
-        request.addValue_forHTTPHeaderField(
-            ns_string!("application/json"),
-            ns_string!("Content-Type"),
-        );
+impl Transport for UrlSessionTransport {
+    fn send(&self, request: Request, completion_handler: Box<dyn FnOnce(Result<Response, Error>)>) {
+        let ns_request = ns_request(&request);
 
+        let url = request.url;
         let completion_handler = Cell::new(Some(completion_handler));
         let block = RcBlock::new(
             move |body: *mut NSData, response: *mut NSURLResponse, error: *mut NSError| {
@@ -103,7 +187,14 @@ impl Session {
                     .take()
                     .expect("completion handler called twice");
                 if let Some(error) = unsafe { error.as_ref() } {
-                    return completion_handler(Err(error.retain()));
+                    return completion_handler(Err(if error.code() == NSURLErrorCancelled {
+                        Error::Cancelled { url: url.clone() }
+                    } else {
+                        Error::Transport {
+                            url: url.clone(),
+                            description: error.localizedDescription().to_string(),
+                        }
+                    }));
                 }
 
                 let response =
@@ -113,185 +204,152 @@ impl Session {
                 let response = response
                     .downcast_ref::<NSHTTPURLResponse>()
                     .expect("invalid kind of NSHTTPURLResponse");
-                let status_code = response.statusCode();
-                if !(200..300).contains(&status_code) {
-                    // TODO: Attempt to parse body here?
-                    let dict = NSDictionary::from_retained_objects(
-                        unsafe { &[NSURLErrorKey, NSLocalizedDescriptionKey] },
-                        &[
-                            Retained::into_super(Retained::into_super(url.retain())),
-                            Retained::into_super(Retained::into_super(
-                                NSHTTPURLResponse::localizedStringForStatusCode(status_code),
-                            )),
-                        ],
-                    );
-                    let error = unsafe {
-                        NSError::errorWithDomain_code_userInfo(
-                            ns_string!(HTTP_STATUS_CODE_DOMAIN),
-                            status_code,
-                            Some(&*dict),
-                        )
-                    };
-                    return completion_handler(Err(error));
-                }
-
-                let json = NSJSONSerialization::JSONObjectWithData_options_error(
-                    body,
-                    NSJSONReadingOptions::empty(),
-                );
-                let json = match json {
-                    Ok(json) => json,
-                    Err(err) => return completion_handler(Err(err)),
-                };
-
-                let parse_error = |json: &AnyObject| {
-                    let Some(json) = json.downcast_ref::<NSDictionary>() else {
-                        let dict = NSDictionary::from_retained_objects(
-                            unsafe { &[NSURLErrorKey, NSLocalizedDescriptionKey] },
-                            &[
-                                Retained::into_super(Retained::into_super(url.retain())),
-                                Retained::into_super(Retained::into_super(
-                                    ns_string!("invalid error response object").copy(),
-                                )),
-                            ],
-                        );
-                        return unsafe {
-                            NSError::errorWithDomain_code_userInfo(
-                                ns_string!(HUE_API_ERROR),
-                                0,
-                                Some(&dict),
-                            )
-                        };
-                    };
-                    let status_code = json
-                        .objectForKey(ns_string!("type"))
-                        .and_then(|n| n.downcast::<NSNumber>().ok())
-                        .map(|n| n.as_isize())
-                        .unwrap_or(0);
-                    let description = json
-                        .objectForKey(ns_string!("description"))
-                        .map(|s| {
-                            s.downcast::<NSString>().unwrap_or_else(|_| {
-                                ns_string!("incorrect error description type").copy()
-                            })
-                        })
-                        .unwrap_or_else(|| ns_string!("no error description").copy());
-
-                    // TODO: Hue error is not localized
-                    let dict = NSDictionary::from_retained_objects(
-                        unsafe { &[NSURLErrorKey, NSLocalizedDescriptionKey] },
-                        &[
-                            Retained::into_super(Retained::into_super(url.retain())),
-                            Retained::into_super(Retained::into_super(description.copy())),
-                        ],
-                    );
-                    unsafe {
-                        NSError::errorWithDomain_code_userInfo(
-                            ns_string!(HUE_API_ERROR),
-                            status_code,
-                            Some(&dict),
-                        )
-                    }
-                };
-
-                if let Some(array) = json.downcast_ref::<NSArray>() {
-                    let result = NSMutableArray::arrayWithCapacity(array.len());
-
-                    // Scan the array for errors
-                    for item in array {
-                        if let Some(dict) = item.downcast_ref::<NSDictionary>() {
-                            if let Some(error) = dict.objectForKey(ns_string!("error")) {
-                                return completion_handler(Err(parse_error(&error)));
-                            }
-
-                            // Unwrap "success" key, if present
-                            if let Some(item) = dict.objectForKey(ns_string!("success")) {
-                                result.addObject(&*item);
-                                continue;
-                            }
-                        }
-
-                        result.addObject(&item);
-                    }
-
-                    completion_handler(Ok(Retained::into_super(Retained::into_super(
-                        Retained::into_super(result),
-                    ))))
-                } else if let Some(dict) = json.downcast_ref::<NSDictionary>() {
-                    if let Some(error) = dict.objectForKey(ns_string!("error")) {
-                        return completion_handler(Err(parse_error(&error)));
-                    }
+                let (fields, values) = response.allHeaderFields().to_vecs();
+                let headers = fields
+                    .iter()
+                    .zip(&values)
+                    .filter_map(|(field, value)| {
+                        let field = field.downcast_ref::<NSString>()?.to_string();
+                        let value = value.downcast_ref::<NSString>()?.to_string();
+                        Some((field.to_ascii_lowercase(), value))
+                    })
+                    .collect();
 
-                    // Unwrap "success" key, if present
-                    if let Some(json) = dict.objectForKey(ns_string!("success")) {
-                        return completion_handler(Ok(json));
-                    }
-
-                    completion_handler(Ok(json))
-                } else {
-                    panic!("NSJSONSerialization neither returned an array nor a dictionary")
-                }
+                completion_handler(Ok(Response {
+                    status_code: response.statusCode() as u16,
+                    headers,
+                    body: body.to_vec(),
+                }))
             },
         );
 
         let task = unsafe {
             self.url_session
-                .dataTaskWithRequest_completionHandler(&request, &block)
+                .dataTaskWithRequest_completionHandler(&ns_request, &block)
         };
         task.resume();
-        Retained::into_super(task)
     }
 
-    pub fn connect(
-        &self,
-        completion_handler: impl FnOnce(Result<(), Retained<NSError>>) + 'static,
-    ) -> Retained<NSURLSessionTask> {
-        let json = NSDictionary::from_retained_objects(
-            &[ns_string!("devicetype")],
-            &[ns_string!("test").copy()],
-        );
-        let username_rc = Rc::clone(&self.username);
-        self.request(
-            ns_string!("POST"),
-            ns_string!("/api"),
-            Some(&json),
-            move |res| {
-                completion_handler(res.map(|obj| {
-                    let array = obj
-                        .downcast_ref::<NSArray>()
-                        .unwrap_or_else(|| todo!("invalid response: {obj:?}"));
-
-                    let dict = array
-                        .objectAtIndex(0)
-                        .downcast::<NSDictionary>()
-                        .unwrap_or_else(|_| todo!("invalid response: {array:?}"));
-
-                    let username = dict
-                        .objectForKey(ns_string!("username"))
-                        .expect("no username")
-                        .downcast::<NSString>()
-                        .unwrap_or_else(|_| todo!("invalid username: {dict:?}"));
-
-                    dbg!(&username);
-
-                    *username_rc.borrow_mut() = Some(username);
-                }))
-            },
-        )
+    fn run_after(&self, delay: Duration, f: Box<dyn FnOnce()>) {
+        schedule_timer(delay, f);
     }
+}
 
-    pub fn authenticated_path(&self, path: &str) -> Retained<NSString> {
-        let res = NSString::from_str(&format!(
-            "/api/{}{path}",
-            self.username
-                .borrow()
-                .as_deref()
-                .unwrap_or_else(|| ns_string!("not-set"))
-        ));
-        res
+/// Call `f` after `delay` from the current run loop.
+fn schedule_timer(delay: Duration, f: Box<dyn FnOnce()>) {
+    let f = Cell::new(Some(f));
+    let block = RcBlock::new(move |_timer| {
+        if let Some(f) = f.take() {
+            f();
+        }
+    });
+    // SAFETY: The timer is scheduled on the current run loop, so the
+    // block is only called on this thread.
+    unsafe {
+        NSTimer::scheduledTimerWithTimeInterval_repeats_block(delay.as_secs_f64(), false, &block)
+    };
+}
+
+/// Answers requests from a fixture, and waits on the run loop instead of
+/// blocking it.
+#[derive(Debug)]
+struct ReplayTransport(Replay);
+
+impl Transport for ReplayTransport {
+    fn send(&self, request: Request, completion_handler: Box<dyn FnOnce(Result<Response, Error>)>) {
+        self.0.send(request, completion_handler)
+    }
+
+    fn run_after(&self, delay: Duration, f: Box<dyn FnOnce()>) {
+        schedule_timer(delay, f);
+    }
+}
+
+/// A [`core::Session`] over an `NSURLSession`, or over a fixture file.
+#[derive(Debug, Clone)]
+pub struct Session {
+    session: core::Session,
+    /// Not set when replaying a fixture.
+    url_session: Option<Retained<NSURLSession>>,
+}
+
+impl Session {
+    /// `log` is where to write the trace, if [`Settings::trace`] is set.
+    pub fn new(
+        mtm: MainThreadMarker,
+        client: Client,
+        settings: &Settings,
+        log: Option<Rc<RotatingLog>>,
+    ) -> Self {
+        // Fall back to the bridge if the fixture can't be loaded
+        let replay = settings
+            .replay_fixture
+            .as_deref()
+            .and_then(|path| match Replay::load(path) {
+                Ok(replay) => Some(replay),
+                Err(err) => {
+                    eprintln!("failed loading fixture {}: {err}", path.display());
+                    None
+                }
+            });
+        let (mut transport, url_session): (Rc<dyn Transport>, _) = match replay {
+            Some(replay) => (Rc::new(ReplayTransport(replay)), None),
+            None => {
+                let transport = UrlSessionTransport::new(mtm, client.clone());
+                let url_session = transport.url_session.clone();
+                (Rc::new(transport), Some(url_session))
+            }
+        };
+
+        if let Some(path) = &settings.record_fixture {
+            match Recorder::new(transport.clone(), client.clone(), path) {
+                Ok(recorder) => {
+                    recorder.set_on_error(|err| eprintln!("failed recording fixture: {err}"));
+                    transport = Rc::new(recorder);
+                }
+                Err(err) => eprintln!("failed opening fixture {}: {err}", path.display()),
+            }
+        }
+        if let (Some(level), Some(log)) = (settings.trace, log) {
+            transport = Rc::new(Tracer::new(
+                transport,
+                client.clone(),
+                level,
+                move |trace| {
+                    if let Err(err) = log.write_line(&trace.to_string()) {
+                        eprintln!("failed writing trace: {err}");
+                    }
+                },
+            ));
+        }
+
+        let session = core::Session::new(transport, client);
+        session.set_on_retry(|err, after| {
+            eprintln!("retrying in {}ms: {err}", after.as_millis());
+        });
+        Self {
+            session,
+            url_session,
+        }
     }
 
     pub fn destroy(&self) {
-        self.url_session.invalidateAndCancel();
+        if let Some(url_session) = &self.url_session {
+            url_session.invalidateAndCancel();
+        }
+    }
+}
+
+impl AsRef<core::Session> for Session {
+    fn as_ref(&self) -> &core::Session {
+        &self.session
+    }
+}
+
+impl Deref for Session {
+    type Target = core::Session;
+
+    fn deref(&self) -> &core::Session {
+        &self.session
     }
 }
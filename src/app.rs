@@ -0,0 +1,484 @@
+use std::cell::OnceCell;
+use std::mem;
+use std::path::Path;
+use std::rc::Rc;
+use std::thread;
+
+use dispatch2::{DispatchQueue, MainThreadBound};
+use menhue_core::api::Client;
+use menhue_core::cache::LightCache;
+use menhue_core::discovery::{self, Discovered};
+use menhue_core::pairing::{self, PairingError};
+use menhue_core::registry::{Bridge, Registry, Section};
+use menhue_core::settings::Settings;
+use menhue_core::state::{Event, StateMachine};
+use menhue_core::trace::RotatingLog;
+use objc2::rc::Retained;
+use objc2::runtime::{AnyObject, ProtocolObject};
+use objc2::{define_class, msg_send, DeclaredClass, MainThreadOnly, Message};
+use objc2_app_kit::{NSApplication, NSApplicationActivationPolicy, NSApplicationDelegate};
+use objc2_foundation::{
+    MainThreadMarker, NSNotification, NSObject, NSObjectProtocol, NSProcessInfo,
+};
+
+use crate::api::Session;
+use crate::event_stream::EventStream;
+use crate::keychain;
+use crate::menu::MenuDelegate;
+use crate::preferences;
+
+#[derive(Debug)]
+pub struct Ivars {
+    /// The bridge that we pair with, if needed.
+    session: Session,
+    /// That bridge, and the already paired bridges in
+    /// [`Settings::extra_bridges`].
+    registry: Registry<Session>,
+    menu: OnceCell<Retained<MenuDelegate>>,
+    state: StateMachine,
+    cache: LightCache,
+    live_updates: bool,
+    event_stream: OnceCell<EventStream>,
+    /// How to find the bridge, if no host was configured.
+    discovery: Option<discovery::Options>,
+}
+
+define_class!(
+    #[unsafe(super(NSObject))]
+    #[thread_kind = MainThreadOnly]
+    #[name = "AppDelegate"]
+    #[ivars = Ivars]
+    pub struct AppDelegate;
+
+    unsafe impl NSObjectProtocol for AppDelegate {}
+
+    unsafe impl NSApplicationDelegate for AppDelegate {
+        #[unsafe(method(applicationDidFinishLaunching:))]
+        fn _did_finish_launching(&self, _notification: &NSNotification) {
+            self.init();
+        }
+
+        #[unsafe(method(applicationWillTerminate:))]
+        fn _will_terminate(&self, _notification: &NSNotification) {
+            self.destroy();
+        }
+    }
+
+    /// Menu actions.
+    impl AppDelegate {
+        #[unsafe(method(openPreferences:))]
+        fn _open_preferences(&self, _sender: Option<&AnyObject>) {
+            let mtm = MainThreadMarker::from(self);
+            preferences::open_preferences(mtm);
+        }
+
+        #[unsafe(method(installUpdates:))]
+        fn _install_updates(&self, _sender: Option<&AnyObject>) {
+            self.install_updates();
+        }
+    }
+);
+
+impl AppDelegate {
+    fn new(mtm: MainThreadMarker) -> Retained<Self> {
+        let mut settings = Settings::from_env();
+        if settings.replay_fixture.is_some() {
+            // Only used for building URLs
+            settings
+                .host
+                .get_or_insert_with(|| "bridge.invalid".to_string());
+        }
+        let discovery = settings.host.is_none().then(|| discovery::Options {
+            nupnp: settings.discovery_url.clone(),
+            ..Default::default()
+        });
+        let client = Client::new(settings.host.clone(), settings.username.clone());
+        client.set_clientkey(settings.clientkey.clone());
+        client.set_bridge_id(settings.bridge_id.clone());
+
+        let log = settings.trace.and_then(|_| open_trace_log(&settings));
+        let session = Session::new(mtm, client, &settings, log.clone());
+        let registry = Registry::new();
+        if settings.host.is_some() {
+            add_bridge(&registry, session.clone());
+        }
+        for bridge in &settings.extra_bridges {
+            let client = Client::new(Some(bridge.host.clone()), bridge.username.clone());
+            client.set_clientkey(bridge.clientkey.clone());
+            client.set_bridge_id(bridge.bridge_id.clone());
+            if client.username().is_none() {
+                // Paired before, like the bridge above
+                match bridge
+                    .bridge_id
+                    .as_deref()
+                    .and_then(keychain::load_credentials)
+                {
+                    Some(credentials) => {
+                        client.set_username(Some(credentials.username));
+                        if credentials.clientkey.is_some() {
+                            client.set_clientkey(credentials.clientkey);
+                        }
+                    }
+                    None => eprintln!(
+                        "no credentials for {}, set its username or bridge id in EXTRA_BRIDGES",
+                        bridge.host
+                    ),
+                }
+            }
+            add_bridge(&registry, Session::new(mtm, client, &settings, log.clone()));
+        }
+
+        let this = mtm.alloc().set_ivars(Ivars {
+            session,
+            registry,
+            menu: OnceCell::new(),
+            state: StateMachine::new(),
+            cache: LightCache::new(),
+            live_updates: settings.live_updates,
+            event_stream: OnceCell::new(),
+            discovery,
+        });
+        unsafe { msg_send![super(this), init] }
+    }
+
+    fn init(&self) {
+        let state = &self.ivars().state;
+        self.ivars()
+            .menu
+            .set(MenuDelegate::new(
+                self,
+                self.ivars().registry.clone(),
+                state.clone(),
+                self.ivars().cache.clone(),
+            ))
+            .expect("only initialized menu once");
+
+        state.handle(Event::Launched);
+        if let Some(options) = &self.ivars().discovery {
+            self.discover_bridge(options.clone());
+            return;
+        }
+        self.connect();
+    }
+
+    /// Find the bridge on a background thread, as that takes a few
+    /// seconds, and connect to it if there's exactly one.
+    fn discover_bridge(&self, options: discovery::Options) {
+        // TODO: Let the user choose
+        let this = MainThreadBound::new(self.retain(), MainThreadMarker::from(self));
+        thread::spawn(move || {
+            let discovered = discovery::discover(&options);
+            DispatchQueue::main().exec_async(move || {
+                let mtm = MainThreadMarker::new().expect("running on the main queue");
+                this.into_inner(mtm).discovered(discovered);
+            });
+        });
+    }
+
+    fn discovered(&self, discovered: Discovered) {
+        for err in &discovered.errors {
+            eprintln!("failed discovering bridges: {err}");
+        }
+        let bridge = match &*discovered.bridges {
+            [bridge] => bridge,
+            [] => {
+                eprintln!("found no bridges, set HOST to the address of the bridge");
+                self.ivars()
+                    .state
+                    .handle(Event::Failed("found no bridges".to_string()));
+                return;
+            }
+            bridges => {
+                eprintln!("found several bridges, set HOST to one of: {bridges:?}");
+                self.ivars()
+                    .state
+                    .handle(Event::Failed("found several bridges".to_string()));
+                return;
+            }
+        };
+
+        let session = &self.ivars().session;
+        let client = session.client();
+        client.set_host(Some(bridge.host()));
+        if client.bridge_id().is_none() {
+            client.set_bridge_id(bridge.bridge_id.clone());
+        }
+        add_bridge(&self.ivars().registry, session.clone());
+        self.connect();
+    }
+
+    /// Log in to the bridge, pairing with it if needed.
+    fn connect(&self) {
+        let state = &self.ivars().state;
+        self.fetch_bridge_names();
+        if self.ivars().session.client().username().is_some() {
+            self.logged_in();
+            return;
+        }
+        state.handle(Event::Failed("no username".to_string()));
+
+        // TODO: Show login dialog
+        state.handle(Event::LogIn);
+        // Check that we're talking to a bridge before asking the user to
+        // press its button
+        let state = state.clone();
+        let this = self.retain();
+        self.ivars().session.config(move |res| {
+            let config = match res {
+                Ok(config) => config,
+                Err(err) => {
+                    eprintln!("failed checking the bridge: {err}");
+                    state.handle(Event::Failed(err.to_string()));
+                    return;
+                }
+            };
+            let client = this.ivars().session.client();
+            match client.bridge_id() {
+                Some(bridge_id) if !bridge_id.eq_ignore_ascii_case(&config.bridgeid) => {
+                    let err = format!("expected bridge {bridge_id}, found {}", config.bridgeid);
+                    eprintln!("failed checking the bridge: {err}");
+                    state.handle(Event::Failed(err));
+                    return;
+                }
+                Some(_) => {}
+                None => client.set_bridge_id(Some(config.bridgeid.clone())),
+            }
+            if this.ivars().live_updates && !config.supports_v2() {
+                eprintln!(
+                    "the bridge is too old for live updates (API version {})",
+                    config.apiversion
+                );
+            }
+            // Paired before
+            if let Some(credentials) = keychain::load_credentials(&config.bridgeid) {
+                client.set_username(Some(credentials.username));
+                if credentials.clientkey.is_some() {
+                    client.set_clientkey(credentials.clientkey);
+                }
+                this.logged_in();
+                return;
+            }
+            this.pair();
+        });
+    }
+
+    /// Start using the bridge, now that we have a username.
+    fn logged_in(&self) {
+        self.ivars().state.handle(Event::Succeeded);
+        self.check_for_updates();
+        self.start_event_stream();
+    }
+
+    fn pair(&self) {
+        let hostname = NSProcessInfo::processInfo().hostName().to_string();
+        let state = self.ivars().state.clone();
+        let this = self.retain();
+        self.ivars().session.pair(
+            pairing::devicetype(&hostname),
+            |remaining| {
+                eprintln!(
+                    "press the link button on the bridge ({}s left)",
+                    remaining.as_secs()
+                );
+            },
+            move |res| match res {
+                Ok(credentials) => {
+                    eprintln!("paired with the bridge");
+                    // Checked before pairing
+                    let bridge_id = this.ivars().session.client().bridge_id();
+                    let bridge_id = bridge_id.expect("bridge id known when pairing");
+                    if let Err(err) = keychain::save_credentials(&bridge_id, &credentials) {
+                        eprintln!(
+                            "failed storing the credentials, will pair again next time: {err}"
+                        );
+                    }
+                    this.logged_in();
+                }
+                Err(PairingError::Api(err)) if err.is_unauthorized() => {
+                    eprintln!("failed pairing: {err}");
+                    state.handle(Event::PermissionDenied);
+                }
+                Err(err) => {
+                    eprintln!("failed pairing: {err}");
+                    state.handle(Event::Failed(err.to_string()));
+                }
+            },
+        );
+    }
+
+    fn start_event_stream(&self) {
+        if !self.ivars().live_updates {
+            return;
+        }
+        // The bridge's certificate is checked against its id, which isn't
+        // known yet on first launch
+        let session = &self.ivars().session;
+        if session.client().bridge_id().is_none() {
+            let this = self.retain();
+            session.config(move |res| match res {
+                Ok(config) => {
+                    let client = this.ivars().session.client();
+                    client.set_bridge_id(Some(config.bridgeid));
+                    this.start_event_stream();
+                }
+                Err(err) => eprintln!("not starting live updates: {err}"),
+            });
+            return;
+        }
+
+        let mtm = MainThreadMarker::from(self);
+        let menu = self.ivars().menu.get().expect("menu initialized").clone();
+        let registry = self.ivars().registry.clone();
+        let cache = self.ivars().cache.clone();
+        let event_stream = EventStream::start(
+            mtm,
+            session.client().clone(),
+            self.ivars().cache.clone(),
+            {
+                let menu = menu.clone();
+                move || seed_cache(&registry, &cache, &menu)
+            },
+            move || menu.lights_changed(),
+        );
+        if self.ivars().event_stream.set(event_stream).is_err() {
+            unreachable!("only started event stream once");
+        }
+    }
+
+    /// Show the bridges' own names instead of their hosts, and stop showing
+    /// bridges that aren't the ones we expected.
+    fn fetch_bridge_names(&self) {
+        for bridge in self.ivars().registry.bridges() {
+            let registry = self.ivars().registry.clone();
+            let client = bridge.session.client().clone();
+            bridge.session.config(move |res| {
+                let config = match res {
+                    Ok(config) => config,
+                    Err(err) => {
+                        eprintln!("failed fetching the name of {}: {err}", bridge.name);
+                        return;
+                    }
+                };
+                match client.bridge_id() {
+                    Some(bridge_id) if !bridge_id.eq_ignore_ascii_case(&config.bridgeid) => {
+                        eprintln!(
+                            "not showing {}: expected bridge {bridge_id}, found {}",
+                            bridge.name, config.bridgeid
+                        );
+                        registry.remove(&bridge.key);
+                        return;
+                    }
+                    Some(_) => {}
+                    None => client.set_bridge_id(Some(config.bridgeid.clone())),
+                }
+                registry.set_name(&bridge.key, config.name);
+            });
+        }
+    }
+
+    /// Offer installing firmware updates if any bridge has one ready.
+    fn check_for_updates(&self) {
+        let menu = self.ivars().menu.get().expect("menu initialized");
+        for bridge in self.ivars().registry.bridges() {
+            if bridge.session.client().username().is_none() {
+                continue;
+            }
+            let menu = menu.clone();
+            bridge.session.software_update(move |res| match res {
+                Ok(update) if update.is_ready() => {
+                    eprintln!("a firmware update is ready for {}", bridge.name);
+                    menu.set_update_available(true);
+                }
+                Ok(_) => {}
+                Err(err) => eprintln!("failed checking {} for updates: {err}", bridge.name),
+            });
+        }
+    }
+
+    fn install_updates(&self) {
+        let menu = self.ivars().menu.get().expect("menu initialized");
+        menu.set_update_available(false);
+        for bridge in self.ivars().registry.bridges() {
+            if bridge.session.client().username().is_none() {
+                continue;
+            }
+            bridge.session.install_update(move |res| match res {
+                Ok(true) => eprintln!("installing firmware update on {}", bridge.name),
+                Ok(false) => {}
+                Err(err) => eprintln!("failed installing update on {}: {err}", bridge.name),
+            });
+        }
+    }
+
+    fn destroy(&self) {
+        if let Some(event_stream) = self.ivars().event_stream.get() {
+            event_stream.stop();
+        }
+        for bridge in self.ivars().registry.bridges() {
+            bridge.session.destroy();
+        }
+    }
+}
+
+/// Add the bridge of the session to the registry, keyed by its id if known
+/// and else by its host.
+fn add_bridge(registry: &Registry<Session>, session: Session) {
+    let client = session.client();
+    let host = client.host().expect("bridges have a host");
+    // Bridge ids are reported in both upper and lower case
+    let key = client
+        .bridge_id()
+        .map_or_else(|| host.clone(), |bridge_id| bridge_id.to_ascii_lowercase());
+    registry.add(Bridge {
+        key,
+        name: host,
+        session,
+    });
+}
+
+/// Fill the cache that the event stream keeps current, like opening the
+/// menu does.
+///
+/// The cache is only used when there's a single bridge.
+fn seed_cache(registry: &Registry<Session>, cache: &LightCache, menu: &Retained<MenuDelegate>) {
+    let cache = cache.clone();
+    let menu = menu.clone();
+    registry.lights(move |mut sections| {
+        if let [Section {
+            items: Ok(lights), ..
+        }] = &mut *sections
+        {
+            cache.set_lights(mem::take(lights));
+            menu.lights_changed();
+        }
+    });
+}
+
+/// Open the log for tracing requests, by default in `~/Library/Logs`.
+fn open_trace_log(settings: &Settings) -> Option<Rc<RotatingLog>> {
+    let path = settings.trace_file.clone().or_else(|| {
+        let home = std::env::var_os("HOME")?;
+        Some(Path::new(&home).join("Library/Logs/menhue/requests.log"))
+    })?;
+    // Keep up to 4 MB of logs
+    match RotatingLog::open(&path, 1024 * 1024, 3) {
+        Ok(log) => Some(Rc::new(log)),
+        Err(err) => {
+            eprintln!("failed opening trace log {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+pub fn main() {
+    let mtm: MainThreadMarker = MainThreadMarker::new().unwrap();
+
+    let app = NSApplication::sharedApplication(mtm);
+    app.setActivationPolicy(NSApplicationActivationPolicy::Accessory);
+
+    let delegate = AppDelegate::new(mtm);
+    let object = ProtocolObject::from_ref(&*delegate);
+    app.setDelegate(Some(object));
+
+    app.run();
+}
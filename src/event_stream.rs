@@ -0,0 +1,259 @@
+use std::cell::{Cell, RefCell};
+use std::fmt;
+
+use block2::DynBlock;
+use dispatch2::MainThreadBound;
+use menhue_core::api::Client;
+use menhue_core::cache::LightCache;
+use menhue_core::eventstream::{parse_events, Backoff, Parser};
+use menhue_core::v2;
+use objc2::rc::Retained;
+use objc2::runtime::{AnyObject, ProtocolObject};
+use objc2::{define_class, msg_send, sel, AllocAnyThread, DeclaredClass};
+use objc2_foundation::{
+    ns_string, MainThreadMarker, NSData, NSError, NSHTTPURLResponse, NSObject,
+    NSObjectNSDelayedPerforming, NSObjectProtocol, NSOperationQueue, NSURLAuthenticationChallenge,
+    NSURLCredential, NSURLResponse, NSURLSession, NSURLSessionAuthChallengeDisposition,
+    NSURLSessionConfiguration, NSURLSessionDataDelegate, NSURLSessionDataTask,
+    NSURLSessionDelegate, NSURLSessionResponseDisposition, NSURLSessionTask,
+    NSURLSessionTaskDelegate,
+};
+
+use crate::api::{handle_challenge, ns_request};
+
+pub struct Ivars {
+    client: Client,
+    cache: LightCache,
+    parser: RefCell<Parser>,
+    backoff: RefCell<Backoff>,
+    /// Called when connected, for filling the cache.
+    on_connect: Box<dyn Fn()>,
+    /// Called when a cached light changed.
+    on_change: Box<dyn Fn()>,
+    stopped: Cell<bool>,
+}
+
+define_class!(
+    #[unsafe(super(NSObject))]
+    #[name = "EventStreamDelegate"]
+    #[ivars = MainThreadBound<Ivars>]
+    struct EventStreamDelegate;
+
+    unsafe impl NSObjectProtocol for EventStreamDelegate {}
+
+    unsafe impl NSURLSessionDelegate for EventStreamDelegate {
+        #[unsafe(method(URLSession:didReceiveChallenge:completionHandler:))]
+        fn _did_receive_challenge(
+            &self,
+            _session: &NSURLSession,
+            challenge: &NSURLAuthenticationChallenge,
+            completion_handler: &DynBlock<
+                dyn Fn(NSURLSessionAuthChallengeDisposition, *mut NSURLCredential),
+            >,
+        ) {
+            handle_challenge(&self.inner().client, challenge, completion_handler);
+        }
+    }
+
+    unsafe impl NSURLSessionTaskDelegate for EventStreamDelegate {
+        #[unsafe(method(URLSession:task:didCompleteWithError:))]
+        fn _did_complete(
+            &self,
+            session: &NSURLSession,
+            _task: &NSURLSessionTask,
+            error: Option<&NSError>,
+        ) {
+            self.disconnected(session, error);
+        }
+    }
+
+    unsafe impl NSURLSessionDataDelegate for EventStreamDelegate {
+        #[unsafe(method(URLSession:dataTask:didReceiveResponse:completionHandler:))]
+        fn _did_receive_response(
+            &self,
+            _session: &NSURLSession,
+            _task: &NSURLSessionDataTask,
+            response: &NSURLResponse,
+            completion_handler: &DynBlock<dyn Fn(NSURLSessionResponseDisposition)>,
+        ) {
+            let disposition = if self.connected(response) {
+                NSURLSessionResponseDisposition::Allow
+            } else {
+                NSURLSessionResponseDisposition::Cancel
+            };
+            completion_handler.call((disposition,));
+        }
+
+        #[unsafe(method(URLSession:dataTask:didReceiveData:))]
+        fn _did_receive_data(
+            &self,
+            _session: &NSURLSession,
+            _task: &NSURLSessionDataTask,
+            data: &NSData,
+        ) {
+            self.received(&data.to_vec());
+        }
+    }
+
+    /// Called elsewhere.
+    impl EventStreamDelegate {
+        #[unsafe(method(reconnect:))]
+        fn _reconnect(&self, session: &NSURLSession) {
+            if !self.inner().stopped.get() {
+                self.connect(session);
+            }
+        }
+    }
+);
+
+impl EventStreamDelegate {
+    fn inner(&self) -> &Ivars {
+        // The session's delegate queue is the main queue
+        let mtm = MainThreadMarker::new().expect("event stream used on the main thread");
+        self.ivars().get(mtm)
+    }
+
+    fn connect(&self, session: &NSURLSession) {
+        let request = v2::Client::new(self.inner().client.clone()).event_stream();
+        let task = session.dataTaskWithRequest(&ns_request(&request));
+        task.resume();
+    }
+
+    fn connected(&self, response: &NSURLResponse) -> bool {
+        let inner = self.inner();
+        let status_code = response
+            .downcast_ref::<NSHTTPURLResponse>()
+            .map(|response| response.statusCode())
+            .unwrap_or_default();
+        match status_code {
+            200 => {
+                inner.backoff.borrow_mut().reset();
+                *inner.parser.borrow_mut() = Parser::new();
+                inner.cache.set_connected(true);
+                (inner.on_connect)();
+                true
+            }
+            401 | 403 => {
+                // Reconnecting won't help until we pair again
+                eprintln!("event stream: not authorized, giving up");
+                inner.stopped.set(true);
+                false
+            }
+            status_code => {
+                eprintln!("event stream: HTTP status {status_code}");
+                false
+            }
+        }
+    }
+
+    fn received(&self, data: &[u8]) {
+        let inner = self.inner();
+        let messages = inner.parser.borrow_mut().feed(data);
+        let mut changed = false;
+        for message in messages {
+            match parse_events(&message) {
+                Ok(events) => {
+                    for event in &events {
+                        changed |= inner.cache.apply(event);
+                    }
+                }
+                Err(err) => eprintln!("event stream: invalid message: {err}"),
+            }
+        }
+        if changed {
+            (inner.on_change)();
+        }
+    }
+
+    fn disconnected(&self, session: &NSURLSession, error: Option<&NSError>) {
+        let inner = self.inner();
+        inner.cache.set_connected(false);
+        if inner.stopped.get() {
+            return;
+        }
+
+        let delay = inner.backoff.borrow_mut().next_delay();
+        match error {
+            Some(error) => eprintln!(
+                "event stream: {}, reconnecting in {delay:?}",
+                error.localizedDescription()
+            ),
+            None => eprintln!("event stream: closed, reconnecting in {delay:?}"),
+        }
+        let session: &AnyObject = session;
+        unsafe {
+            self.performSelector_withObject_afterDelay(
+                sel!(reconnect:),
+                Some(session),
+                delay.as_secs_f64(),
+            )
+        };
+    }
+}
+
+/// A subscription to the bridge's event stream, which keeps a [`LightCache`]
+/// up to date.
+pub struct EventStream {
+    url_session: Retained<NSURLSession>,
+    delegate: Retained<EventStreamDelegate>,
+}
+
+impl fmt::Debug for EventStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventStream")
+            .field("url_session", &self.url_session)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EventStream {
+    /// `on_connect` is called each time the stream (re)connects, with the
+    /// cache empty; fill it with [`LightCache::set_lights`] so that events
+    /// can be applied to it.
+    pub fn start(
+        mtm: MainThreadMarker,
+        client: Client,
+        cache: LightCache,
+        on_connect: impl Fn() + 'static,
+        on_change: impl Fn() + 'static,
+    ) -> Self {
+        let delegate = EventStreamDelegate::alloc().set_ivars(MainThreadBound::new(
+            Ivars {
+                client,
+                cache,
+                parser: RefCell::new(Parser::new()),
+                backoff: RefCell::new(Backoff::default()),
+                on_connect: Box::new(on_connect),
+                on_change: Box::new(on_change),
+                stopped: Cell::new(false),
+            },
+            mtm,
+        ));
+        let delegate: Retained<EventStreamDelegate> = unsafe { msg_send![super(delegate), init] };
+
+        let config = NSURLSessionConfiguration::ephemeralSessionConfiguration();
+        config.setAllowsCellularAccess(false);
+        // The request timeout applies between chunks of data, and the bridge
+        // may not send anything for a long time
+        config.setTimeoutIntervalForRequest(24.0 * 60.0 * 60.0);
+        let url_session = unsafe {
+            NSURLSession::sessionWithConfiguration_delegate_delegateQueue(
+                &config,
+                Some(ProtocolObject::from_ref(&*delegate)),
+                Some(&NSOperationQueue::mainQueue()),
+            )
+        };
+        url_session.setSessionDescription(Some(ns_string!("event stream")));
+
+        delegate.connect(&url_session);
+        Self {
+            url_session,
+            delegate,
+        }
+    }
+
+    pub fn stop(&self) {
+        self.delegate.inner().stopped.set(true);
+        self.url_session.invalidateAndCancel();
+    }
+}
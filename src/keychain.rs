@@ -0,0 +1,100 @@
+//! Keeping the credentials for each bridge in the login keychain, so that
+//! we only have to pair once.
+use std::ptr::{self, NonNull};
+
+use menhue_core::pairing::Credentials;
+use objc2_core_foundation::{kCFBooleanTrue, CFData, CFDictionary, CFRetained, CFString, CFType};
+use objc2_security::{
+    errSecItemNotFound, errSecSuccess, kSecAttrAccount, kSecAttrService, kSecClass,
+    kSecClassGenericPassword, kSecMatchLimit, kSecMatchLimitOne, kSecReturnData, kSecValueData,
+    SecItemAdd, SecItemCopyMatching, SecItemDelete,
+};
+use serde_json::{json, Value};
+
+/// The service of our keychain items; the account is the bridge id.
+const SERVICE: &str = "menhue";
+
+/// Build a query for the item of the bridge, with additional attributes.
+fn query(bridge_id: &str, attributes: &[(&CFString, &CFType)]) -> CFRetained<CFDictionary> {
+    let service = CFString::from_str(SERVICE);
+    // Bridge ids are reported in both upper and lower case
+    let account = CFString::from_str(&bridge_id.to_ascii_lowercase());
+    // SAFETY: The constants are immutable.
+    let (mut keys, mut values): (Vec<&CFString>, Vec<&CFType>) = unsafe {
+        (
+            vec![kSecClass, kSecAttrService, kSecAttrAccount],
+            vec![&**kSecClassGenericPassword, &**service, &**account],
+        )
+    };
+    for (key, value) in attributes {
+        keys.push(key);
+        values.push(value);
+    }
+    let query = CFDictionary::<CFString, CFType>::from_slices(&keys, &values);
+    // SAFETY: Only the key and value types are forgotten.
+    unsafe { CFRetained::cast_unchecked(query) }
+}
+
+/// Store the credentials for the bridge, replacing what was stored before.
+pub fn save_credentials(bridge_id: &str, credentials: &Credentials) -> Result<(), String> {
+    let json = json!({
+        "username": credentials.username,
+        "clientkey": credentials.clientkey,
+    });
+    let data = CFData::from_bytes(json.to_string().as_bytes());
+
+    // SAFETY: The queries have the types that the keychain expects.
+    let status = unsafe { SecItemDelete(&query(bridge_id, &[])) };
+    if status != errSecSuccess && status != errSecItemNotFound {
+        return Err(format!(
+            "failed removing old keychain item: OSStatus {status}"
+        ));
+    }
+    let status = unsafe {
+        SecItemAdd(
+            &query(bridge_id, &[(kSecValueData, &**data)]),
+            ptr::null_mut(),
+        )
+    };
+    if status != errSecSuccess {
+        return Err(format!("failed adding keychain item: OSStatus {status}"));
+    }
+    Ok(())
+}
+
+/// The credentials stored for the bridge, if any.
+pub fn load_credentials(bridge_id: &str) -> Option<Credentials> {
+    // SAFETY: The constants are immutable, and `kCFBooleanTrue` is set on
+    // all versions of macOS.
+    let query = unsafe {
+        query(
+            bridge_id,
+            &[
+                (kSecReturnData, &**kCFBooleanTrue.expect("kCFBooleanTrue")),
+                (kSecMatchLimit, &**kSecMatchLimitOne),
+            ],
+        )
+    };
+    let mut result: *const CFType = ptr::null();
+    // SAFETY: The query has the types that the keychain expects, and
+    // `result` is a valid pointer.
+    let status = unsafe { SecItemCopyMatching(&query, &mut result) };
+    if status != errSecSuccess {
+        if status != errSecItemNotFound {
+            eprintln!("failed reading keychain item: OSStatus {status}");
+        }
+        return None;
+    }
+    // SAFETY: The result follows the create rule, so we own it.
+    let result = unsafe { CFRetained::from_raw(NonNull::new(result.cast_mut())?) };
+    let data = result.downcast::<CFData>().ok()?;
+
+    let json: Value = serde_json::from_slice(&data.to_vec()).ok()?;
+    Some(Credentials {
+        username: json.get("username")?.as_str()?.to_string(),
+        clientkey: json
+            .get("clientkey")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+    })
+}
@@ -1,30 +1,31 @@
-use std::{
-    cell::Cell,
-    time::{Duration, Instant},
-};
+use std::cell::RefCell;
 
-use objc2::{
-    define_class, msg_send, rc::Retained, runtime::AnyObject, sel, DeclaredClass, MainThreadOnly,
-    Message,
+use menhue_core::{
+    error::HueErrorKind,
+    light::{Light, StateUpdate},
 };
+use objc2::{define_class, msg_send, rc::Retained, sel, DeclaredClass, MainThreadOnly};
 use objc2_app_kit::{
-    NSLayoutAttribute, NSLayoutConstraint, NSSlider, NSStackView, NSStackViewDistribution,
-    NSTextField, NSUserInterfaceLayoutOrientation, NSView,
+    NSButton, NSControlStateValueOff, NSControlStateValueOn, NSLayoutAttribute, NSLayoutConstraint,
+    NSSlider, NSStackView, NSStackViewDistribution, NSTextField, NSUserInterfaceLayoutOrientation,
+    NSView,
 };
 use objc2_foundation::{
-    ns_string, MainThreadMarker, NSArray, NSCopying, NSDictionary, NSInteger, NSNumber, NSObject,
-    NSObjectNSDelayedPerforming, NSObjectProtocol, NSRunLoopCommonModes, NSSize, NSString,
+    ns_string, MainThreadMarker, NSArray, NSObject, NSObjectProtocol, NSSize, NSString,
 };
 
 use crate::api::Session;
 
 #[derive(Debug)]
 pub struct Ivars {
-    light_id: Retained<NSString>,
+    /// The light as last seen, to check updates against.
+    light: RefCell<Light>,
     view: Retained<NSView>,
-    slider: Retained<NSSlider>,
+    /// Only present if the light can be dimmed.
+    slider: Option<Retained<NSSlider>>,
+    /// On/off switch for lights that can't be dimmed.
+    toggle: Option<Retained<NSButton>>,
     session: Session,
-    last_updated_bri: Cell<Instant>,
 }
 
 define_class!(
@@ -41,24 +42,18 @@ define_class!(
     impl LightController {
         #[unsafe(method(dragSlider:))]
         fn _drag_slider(&self, _slider: &NSSlider) {
-            self.queue_update_bri();
+            self.update_bri_from_slider();
         }
 
-        #[unsafe(method(setBri:))]
-        fn _update_bri_from_slider(&self, _: Option<&AnyObject>) {
-            self.update_bri_from_slider();
+        #[unsafe(method(toggleOn:))]
+        fn _toggle_on(&self, _button: &NSButton) {
+            self.update_on_from_toggle();
         }
     }
 );
 
 impl LightController {
-    pub fn new(
-        light_id: &NSString,
-        name: &NSString,
-        bri: NSInteger,
-        session: Session,
-        mtm: MainThreadMarker,
-    ) -> Retained<Self> {
+    pub fn new(light: &Light, session: Session, mtm: MainThreadMarker) -> Retained<Self> {
         let view = NSView::new(mtm);
         view.setFrameSize(NSSize {
             height: 100.0,
@@ -74,7 +69,7 @@ impl LightController {
         view.addSubview(&stack);
         stack.setTranslatesAutoresizingMaskIntoConstraints(false);
 
-        let label = NSTextField::labelWithString(name, mtm);
+        let label = NSTextField::labelWithString(&NSString::from_str(&light.name), mtm);
         // label.setStringValue(name);
         // label.setBackgroundColor(Some(&NSColor::colorWithRed_green_blue_alpha(
         //     0.0, 0.0, 0.0, 0.0,
@@ -83,15 +78,30 @@ impl LightController {
         // label.setEditable(false);
         stack.addArrangedSubview(&label);
 
-        let slider = NSSlider::new(mtm);
-        slider.setFrameSize(NSSize {
-            height: 50.0,
-            width: 250.0,
-        });
-        slider.setMinValue(0.0);
-        slider.setMaxValue(254.0);
-        slider.setIntegerValue(bri);
-        stack.addArrangedSubview(&slider);
+        // Lights without brightness (e.g. plugs) only get an on/off switch
+        let (slider, toggle) = if let Some(bri) = light.state.bri {
+            let slider = NSSlider::new(mtm);
+            slider.setFrameSize(NSSize {
+                height: 50.0,
+                width: 250.0,
+            });
+            slider.setMinValue(0.0);
+            slider.setMaxValue(254.0);
+            slider.setIntegerValue(bri as isize);
+            stack.addArrangedSubview(&slider);
+            (Some(slider), None)
+        } else {
+            let toggle = unsafe {
+                NSButton::checkboxWithTitle_target_action(ns_string!("On"), None, None, mtm)
+            };
+            toggle.setState(if light.state.is_on() {
+                NSControlStateValueOn
+            } else {
+                NSControlStateValueOff
+            });
+            stack.addArrangedSubview(&toggle);
+            (None, Some(toggle))
+        };
 
         NSLayoutConstraint::activateConstraints(&NSArray::from_retained_slice(&[
             stack
@@ -112,65 +122,104 @@ impl LightController {
         ]));
 
         let this = mtm.alloc().set_ivars(Ivars {
-            light_id: light_id.copy(),
+            light: RefCell::new(light.clone()),
             view,
-            slider: slider.retain(),
+            slider: slider.clone(),
+            toggle: toggle.clone(),
             session,
-            last_updated_bri: Cell::new(Instant::now()),
         });
         let this: Retained<Self> = unsafe { msg_send![super(this), init] };
 
-        unsafe {
-            slider.setTarget(Some(&this));
-            slider.setAction(Some(sel!(dragSlider:)));
+        if let Some(slider) = &slider {
+            unsafe {
+                slider.setTarget(Some(&this));
+                slider.setAction(Some(sel!(dragSlider:)));
+            }
+        }
+        if let Some(toggle) = &toggle {
+            unsafe {
+                toggle.setTarget(Some(&this));
+                toggle.setAction(Some(sel!(toggleOn:)));
+            }
         }
 
         this
     }
 
     fn update_bri_from_slider(&self) {
-        let bri = self.ivars().slider.integerValue();
-        let path = format!("/lights/{}/state", self.ivars().light_id);
-        let json = NSDictionary::from_retained_objects(
-            &[
-                ns_string!("on"),
-                ns_string!("bri"),
-                ns_string!("transitiontime"),
-            ],
-            &[
-                Retained::into_super(Retained::into_super(NSNumber::numberWithBool(bri > 0))),
-                Retained::into_super(Retained::into_super(NSNumber::numberWithInteger(bri))),
-                Retained::into_super(Retained::into_super(NSNumber::numberWithInteger(1))),
-            ],
-        );
-        self.ivars().session.request(
-            ns_string!("PUT"),
-            &self.ivars().session.authenticated_path(&path),
-            Some(&json),
-            move |res| match res {
-                Ok(_obj) => {}
+        let Some(slider) = &self.ivars().slider else {
+            return;
+        };
+        let bri = slider.integerValue();
+        // Lights can't be dimmed to 0, all the way down turns them off
+        let update = if bri > 0 {
+            StateUpdate {
+                on: Some(true),
+                bri: Some(bri.min(254) as u8),
+                transitiontime: Some(1),
+                ..Default::default()
+            }
+        } else {
+            StateUpdate {
+                on: Some(false),
+                transitiontime: Some(1),
+                ..Default::default()
+            }
+        };
+        self.send_update(update);
+    }
+
+    fn update_on_from_toggle(&self) {
+        let Some(toggle) = &self.ivars().toggle else {
+            return;
+        };
+        self.send_update(StateUpdate {
+            on: Some(toggle.state() == NSControlStateValueOn),
+            ..Default::default()
+        });
+    }
+
+    fn send_update(&self, update: StateUpdate) {
+        let light = self.ivars().light.borrow();
+        let res = self
+            .ivars()
+            .session
+            .update_light(&light, update, move |res| match res {
+                // Each attribute is applied separately, so report them as such
+                Ok(result) => {
+                    for error in &result.errors {
+                        if error.kind == HueErrorKind::DeviceOff {
+                            eprintln!("light is off, could not set {}", error.address);
+                        } else {
+                            eprintln!("failed setting light: {error}");
+                        }
+                    }
+                }
                 Err(err) => {
                     eprintln!("failed setting light: {err}");
                 }
-            },
-        );
+            });
+        if let Err(err) = res {
+            eprintln!("not setting light: {err}");
+        }
     }
 
-    fn queue_update_bri(&self) {
-        let interval = 0.050;
-        let now = Instant::now();
-        if (now - self.ivars().last_updated_bri.get())
-            > Duration::from_millis((interval * 1000.0) as u64)
-        {
-            self.ivars().last_updated_bri.set(now);
-            unsafe {
-                self.performSelector_withObject_afterDelay_inModes(
-                    sel!(setBri:),
-                    None,
-                    interval,
-                    &NSArray::from_slice(&[NSRunLoopCommonModes]),
-                )
-            };
+    pub fn light_id(&self) -> String {
+        self.ivars().light.borrow().id.clone()
+    }
+
+    /// Show a new state of the light, e.g. after it was changed elsewhere.
+    pub fn update(&self, light: &Light) {
+        *self.ivars().light.borrow_mut() = light.clone();
+        if let (Some(slider), Some(bri)) = (&self.ivars().slider, light.state.bri) {
+            slider.setIntegerValue(bri as isize);
+        }
+        if let Some(toggle) = &self.ivars().toggle {
+            toggle.setState(if light.state.is_on() {
+                NSControlStateValueOn
+            } else {
+                NSControlStateValueOff
+            });
         }
     }
 
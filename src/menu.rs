@@ -1,27 +1,31 @@
-#![deny(unsafe_op_in_unsafe_fn)]
 use std::cell::RefCell;
 
+use menhue_core::cache::LightCache;
+use menhue_core::light::Light;
+use menhue_core::registry::{Registry, Section};
+use menhue_core::state::{Event, State, StateMachine};
 use objc2::rc::Retained;
-use objc2::runtime::{AnyObject, ProtocolObject};
+use objc2::runtime::ProtocolObject;
 use objc2::{define_class, msg_send, sel, DeclaredClass, MainThreadOnly, Message};
 use objc2_app_kit::{
     NSImage, NSMenu, NSMenuDelegate, NSMenuItem, NSStatusBar, NSStatusItem, NSStatusItemBehavior,
     NSVariableStatusItemLength,
 };
 use objc2_foundation::{
-    ns_string, MainThreadMarker, NSDictionary, NSMutableArray, NSNumber, NSObject,
-    NSObjectProtocol, NSString,
+    ns_string, MainThreadMarker, NSMutableArray, NSObject, NSObjectProtocol, NSString,
 };
 
 use crate::api::Session;
+use crate::app::AppDelegate;
 use crate::light_controller::LightController;
-use crate::AppDelegate;
 
 #[derive(Debug)]
 pub struct Ivars {
     _status_bar_item: Retained<NSStatusItem>,
     menu: Retained<NSMenu>,
-    session: Session,
+    registry: Registry<Session>,
+    state: StateMachine,
+    cache: LightCache,
     /// Keep references to the light controllers around
     light_controllers: RefCell<Retained<NSMutableArray<LightController>>>,
 }
@@ -42,14 +46,25 @@ define_class!(
         fn menuNeedsUpdate(&self, _menu: &NSMenu) {
             self.needs_update();
         }
+
+        #[unsafe(method(menuDidClose:))]
+        fn menuDidClose(&self, _menu: &NSMenu) {
+            self.ivars().state.handle(Event::Closed);
+        }
     }
 );
 
 const TAG_LOADING: isize = 1;
 const TAG_LIGHT: isize = 2;
+const TAG_UPDATE: isize = 3;
 
 impl MenuDelegate {
-    pub fn new(app_delegate: &AppDelegate, session: Session) -> Retained<Self> {
+    pub fn new(
+        app_delegate: &AppDelegate,
+        registry: Registry<Session>,
+        state: StateMachine,
+        cache: LightCache,
+    ) -> Retained<Self> {
         let mtm = MainThreadMarker::from(app_delegate);
         let status_bar = NSStatusBar::systemStatusBar();
         let status_bar_item = status_bar.statusItemWithLength(NSVariableStatusItemLength);
@@ -76,7 +91,9 @@ impl MenuDelegate {
         let this = mtm.alloc().set_ivars(Ivars {
             _status_bar_item: status_bar_item,
             menu,
-            session,
+            registry,
+            state,
+            cache,
             light_controllers: RefCell::new(NSMutableArray::new()),
         });
         let this: Retained<Self> = unsafe { msg_send![super(this), init] };
@@ -93,6 +110,16 @@ impl MenuDelegate {
         let item = NSMenuItem::separatorItem(mtm);
         menu.addItem(&item);
 
+        let item = NSMenuItem::new(mtm);
+        item.setTitle(ns_string!("Install Bridge Update"));
+        item.setHidden(true);
+        item.setTag(TAG_UPDATE);
+        unsafe {
+            item.setTarget(Some(app_delegate));
+            item.setAction(Some(sel!(installUpdates:)));
+        }
+        menu.addItem(&item);
+
         let item = NSMenuItem::new(mtm);
         item.setTitle(ns_string!("Preferences..."));
         unsafe {
@@ -112,16 +139,29 @@ impl MenuDelegate {
         this
     }
 
-    fn set_loading(&self, loading: bool) {
+    fn handle(&self, event: Event) -> bool {
+        let handled = self.ivars().state.handle(event);
         let item = self
             .ivars()
             .menu
             .itemWithTag(TAG_LOADING)
             .expect("loading item");
-        item.setHidden(!loading);
+        item.setHidden(!self.ivars().state.state().is_loading());
+        handled
+    }
+
+    /// Show or hide the item for installing firmware updates.
+    pub fn set_update_available(&self, available: bool) {
+        let item = self
+            .ivars()
+            .menu
+            .itemWithTag(TAG_UPDATE)
+            .expect("update item");
+        item.setHidden(!available);
     }
 
-    fn update_lights(&self, obj: &AnyObject) {
+    /// Show the lights, with a header per bridge if there are several.
+    fn update_lights(&self, sections: &[Section<Light>]) {
         let mtm = MainThreadMarker::from(self);
         let menu = &self.ivars().menu;
         let light_controllers = self.ivars().light_controllers.borrow_mut();
@@ -132,82 +172,154 @@ impl MenuDelegate {
         }
         light_controllers.removeAllObjects();
 
-        let data = obj
-            .downcast_ref::<NSDictionary>()
-            .expect("invalid response");
-
         // Add new menus
-        for (i, light_id) in data.keys().enumerate() {
-            let light_id = light_id.downcast::<NSString>().expect("invalid response");
-
-            let dict = data
-                .objectForKey(&light_id)
-                .unwrap()
-                .downcast::<NSDictionary>()
-                .expect("invalid response");
-
-            let name = dict
-                .objectForKey(ns_string!("name"))
-                .expect("name")
-                .downcast::<NSString>()
-                .expect("invalid name");
-
-            let state = dict
-                .objectForKey(ns_string!("state"))
-                .expect("state")
-                .downcast::<NSDictionary>()
-                .expect("invalid state");
-
-            let reachable = state
-                .objectForKey(ns_string!("reachable"))
-                .expect("reachable")
-                .downcast::<NSNumber>()
-                .expect("invalid reachable")
-                .as_bool();
-
-            if !reachable {
-                // Ignore light if not reachable
-                continue;
+        let mut index = 1;
+        let mut insert = |item: &NSMenuItem| {
+            item.setTag(TAG_LIGHT);
+            menu.insertItem_atIndex(item, index);
+            index += 1;
+        };
+        for section in sections {
+            if sections.len() > 1 {
+                let item = NSMenuItem::new(mtm);
+                item.setTitle(&NSString::from_str(&section.name));
+                item.setEnabled(false);
+                insert(&item);
             }
+            let lights = match &section.items {
+                Ok(lights) => lights,
+                Err(err) => {
+                    let item = NSMenuItem::new(mtm);
+                    item.setTitle(&NSString::from_str(&format!(
+                        "Failed loading lights: {err}"
+                    )));
+                    item.setEnabled(false);
+                    insert(&item);
+                    continue;
+                }
+            };
 
-            let bri = state
-                .objectForKey(ns_string!("bri"))
-                .expect("bri")
-                .downcast::<NSNumber>()
-                .expect("invalid bri")
-                .integerValue();
+            for light in lights {
+                if !light.is_reachable() {
+                    // Ignore light if not reachable
+                    continue;
+                }
+                let Some(session) = self.ivars().registry.session(&light.bridge) else {
+                    continue;
+                };
 
-            let light_control =
-                LightController::new(&light_id, &name, bri, self.ivars().session.clone(), mtm);
+                let light_control = LightController::new(light, session, mtm);
 
-            let item = NSMenuItem::new(mtm);
-            item.setTitle(&name);
-            item.setView(Some(light_control.view()));
-            item.setTag(TAG_LIGHT);
-            menu.insertItem_atIndex(&item, i as isize + 1);
+                let item = NSMenuItem::new(mtm);
+                item.setTitle(&NSString::from_str(&light.name));
+                item.setView(Some(light_control.view()));
+                insert(&item);
 
-            light_controllers.addObject(&light_control);
+                light_controllers.addObject(&light_control);
+            }
+        }
+    }
+
+    /// The cached lights, as the only section.
+    ///
+    /// The cache is only filled when there's a single bridge.
+    fn cached_lights(&self) -> Option<Vec<Section<Light>>> {
+        let lights = self.ivars().cache.lights()?;
+        let bridge = self.ivars().registry.bridges().into_iter().next()?;
+        Some(vec![Section {
+            bridge: bridge.key,
+            name: bridge.name,
+            items: Ok(lights),
+        }])
+    }
+
+    /// Called when the event stream changed a cached light.
+    pub fn lights_changed(&self) {
+        if self.ivars().state.state() != State::MenuOpen {
+            return;
+        }
+        let Some(sections) = self.cached_lights() else {
+            return;
+        };
+        let lights = sections[0].items.as_deref().unwrap_or_default();
+
+        let reachable: Vec<&Light> = lights.iter().filter(|light| light.is_reachable()).collect();
+        let light_controllers = self.ivars().light_controllers.borrow();
+        let same_lights = light_controllers.len() == reachable.len()
+            && light_controllers
+                .iter()
+                .zip(&reachable)
+                .all(|(controller, light)| controller.light_id() == light.id);
+        if !same_lights {
+            drop(light_controllers);
+            self.update_lights(&sections);
+            return;
+        }
+
+        // Update in place, so that we don't interrupt e.g. dragging a slider
+        for (controller, light) in light_controllers.iter().zip(reachable) {
+            controller.update(light);
         }
     }
 
     fn needs_update(&self) {
-        self.set_loading(true);
+        if !self.handle(Event::Opened) {
+            eprintln!(
+                "not fetching lights in state {:?}",
+                self.ivars().state.state()
+            );
+            return;
+        }
+
+        // Kept up to date by the event stream
+        if let Some(sections) = self.cached_lights() {
+            self.handle(Event::Succeeded);
+            self.update_lights(&sections);
+            return;
+        }
 
         let this = self.retain();
-        self.ivars().session.request(
-            ns_string!("GET"),
-            &self.ivars().session.authenticated_path("/lights"),
-            None,
-            move |res| match res {
-                Ok(obj) => {
-                    this.set_loading(false);
-                    this.update_lights(&obj);
+        self.ivars().registry.lights(move |mut sections| {
+            for section in &sections {
+                if let Err(err) = &section.items {
+                    if err.is_overloaded() {
+                        eprintln!("{} is busy, failed fetching lights: {err}", section.name);
+                    } else if err.is_from_proxy() {
+                        eprintln!("request did not reach {}: {err}", section.name);
+                    } else {
+                        eprintln!("failed fetching lights from {}: {err}", section.name);
+                    }
                 }
-                Err(err) => {
-                    eprintln!("failed fetching lights: {err}");
-                    this.set_loading(false);
+            }
+
+            // Show what we have, unless every bridge failed
+            if !sections.iter().any(|section| section.items.is_ok()) {
+                match sections.first() {
+                    Some(Section {
+                        items: Err(err), ..
+                    }) if err.is_unauthorized() => {
+                        this.handle(Event::PermissionDenied);
+                    }
+                    Some(Section {
+                        items: Err(err), ..
+                    }) => {
+                        this.handle(Event::Failed(err.to_string()));
+                    }
+                    _ => {
+                        this.handle(Event::Failed("no bridges".to_string()));
+                    }
                 }
-            },
-        );
+                return;
+            }
+
+            this.handle(Event::Succeeded);
+            this.update_lights(&sections);
+            if let [Section {
+                items: Ok(lights), ..
+            }] = &mut *sections
+            {
+                this.ivars().cache.set_lights(std::mem::take(lights));
+            }
+        });
     }
 }